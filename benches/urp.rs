@@ -0,0 +1,86 @@
+//! Benchmarks of the Cube and CubeList operations
+//!
+//! Each operation is measured on the functions of sample_files and on
+//! covers generated by CubeList::random with a fixed seed, so that runs
+//! can be compared. Run them with `cargo bench`, or
+//! `cargo bench -- complement` for a single group
+
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use urp::{Cube, CubeList};
+
+/// The sample functions, by file name
+fn sample_functions() -> Vec<(String, CubeList)> {
+    (1..=6)
+        .map(|i| {
+            let file = format!("{}/sample_files/{}.pcn", env!("CARGO_MANIFEST_DIR"), i);
+            (
+                format!("{}.pcn", i),
+                CubeList::read_from_file(&file).unwrap(),
+            )
+        })
+        .collect()
+}
+
+/// The generated covers, by number of variables and Cubes
+fn generated_functions() -> Vec<(String, CubeList)> {
+    [(8, 20), (12, 40), (16, 60), (20, 80)]
+        .iter()
+        .map(|&(num_var, num_cubes)| {
+            (
+                format!("random_{}x{}", num_var, num_cubes),
+                CubeList::random(num_var, num_cubes, 0.33, 1),
+            )
+        })
+        .collect()
+}
+
+fn cube_and(c: &mut Criterion) {
+    let mut group = c.benchmark_group("cube_and");
+    for num_var in [8, 32, 128] {
+        let cubes = CubeList::random(num_var, 128, 0.25, 2);
+        let cubes: Vec<&Cube> = cubes.iter().collect();
+        let pairs: Vec<(&Cube, &Cube)> = cubes.chunks(2).map(|pair| (pair[0], pair[1])).collect();
+        group.bench_with_input(BenchmarkId::from_parameter(num_var), &pairs, |b, pairs| {
+            b.iter(|| {
+                for (cube_x, cube_y) in pairs {
+                    black_box(cube_x.and(cube_y));
+                }
+            })
+        });
+    }
+    group.finish();
+}
+
+fn complement(c: &mut Criterion) {
+    let mut group = c.benchmark_group("complement");
+    for (name, cubelist) in sample_functions().into_iter().chain(generated_functions()) {
+        group.bench_with_input(
+            BenchmarkId::from_parameter(name),
+            &cubelist,
+            |b, cubelist| b.iter(|| black_box(cubelist.complement())),
+        );
+    }
+    group.finish();
+}
+
+fn is_tautology(c: &mut Criterion) {
+    let mut group = c.benchmark_group("is_tautology");
+    // The functions are rarely tautologies, which are decided quickly.
+    // Each function ORed with its complement is a tautology, which has
+    // to be checked through the whole recursion
+    for (name, cubelist) in sample_functions().into_iter().chain(generated_functions()) {
+        let tautology = cubelist.or(&cubelist.complement());
+        group.bench_with_input(BenchmarkId::new("f", &name), &cubelist, |b, cubelist| {
+            b.iter(|| black_box(cubelist.is_tautology()))
+        });
+        group.bench_with_input(
+            BenchmarkId::new("f+f'", &name),
+            &tautology,
+            |b, tautology| b.iter(|| black_box(tautology.is_tautology())),
+        );
+    }
+    group.finish();
+}
+
+criterion_group!(benches, cube_and, complement, is_tautology);
+criterion_main!(benches);
@@ -0,0 +1,260 @@
+//! Computed tables that memoize the results of the URP recursion
+//!
+//! The same sub-covers are reached many times while complementing or
+//! checking a function for tautology, e.g. by cofactoring on x then y and
+//! on y then x. A ComputedTable stores the results for such sub-covers,
+//! keyed by their Cubes in sorted order so that the order in which the
+//! Cubes were found does not matter. A lookup borrows the Cubes, which are
+//! only compared when a stored result has the same hash, and only a stored
+//! result copies them.
+
+use super::cube::Cube;
+use super::cubelist::CubeList;
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+
+/// The default number of entries of each table of a ComputedTable
+pub const DEFAULT_CAPACITY: usize = 1 << 14;
+
+/// Key identifies a function by its number of variables and its sorted
+/// Cubes, with the hash of both. The Cubes are borrowed from the CubeList,
+/// so a lookup copies none of them
+pub(crate) struct Key<'a> {
+    hash: u64,
+    num_var: usize,
+    cubes: Vec<&'a Cube>,
+}
+
+impl<'a> Key<'a> {
+    /// Returns the key of a CubeList
+    pub(crate) fn new(cubelist: &'a CubeList) -> Self {
+        let mut cubes: Vec<&Cube> = cubelist.iter().collect();
+        cubes.sort_unstable();
+        cubes.dedup();
+        // A reference hashes like the Cube, so the hash is the one of the
+        // canonical Cubes
+        let mut hasher = DefaultHasher::new();
+        cubelist.num_var().hash(&mut hasher);
+        cubes.hash(&mut hasher);
+        Key {
+            hash: hasher.finish(),
+            num_var: cubelist.num_var(),
+            cubes,
+        }
+    }
+
+    /// Returns the hash of the key
+    pub(crate) fn hash(&self) -> u64 {
+        self.hash
+    }
+}
+
+/// Entry is a stored key, which owns a copy of its Cubes
+struct Entry {
+    hash: u64,
+    num_var: usize,
+    cubes: Vec<Cube>,
+}
+
+impl Entry {
+    /// Returns the entry of a key
+    fn new(key: &Key) -> Self {
+        Entry {
+            hash: key.hash,
+            num_var: key.num_var,
+            cubes: key.cubes.iter().map(|cube| (*cube).clone()).collect(),
+        }
+    }
+
+    /// Returns true if the entry and the key identify the same function.
+    /// The Cubes are only compared when the hashes are equal
+    fn matches(&self, key: &Key) -> bool {
+        self.hash == key.hash
+            && self.num_var == key.num_var
+            && self.cubes.iter().eq(key.cubes.iter().copied())
+    }
+}
+
+/// CacheStats holds the number of lookups of a table that found
+/// a result, that did not find one, and the number of stored results
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct CacheStats {
+    pub hits: usize,
+    pub misses: usize,
+    pub entries: usize,
+}
+
+/// Table is a direct mapped table of results. The slot of a key is given
+/// by its hash, and a new result replaces the one in its slot, so the
+/// table never grows past its capacity
+struct Table<V> {
+    slots: Mutex<Vec<Option<(Entry, V)>>>,
+    hits: AtomicUsize,
+    misses: AtomicUsize,
+}
+
+impl<V: Clone> Table<V> {
+    /// Returns a new empty table with the given number of slots
+    fn new(capacity: usize) -> Self {
+        Table {
+            slots: Mutex::new((0..capacity.max(1)).map(|_| None).collect()),
+            hits: AtomicUsize::new(0),
+            misses: AtomicUsize::new(0),
+        }
+    }
+
+    /// Returns the result stored for the key, if any
+    fn get(&self, key: &Key) -> Option<V> {
+        let slots = self.slots.lock().unwrap();
+        let index = (key.hash % slots.len() as u64) as usize;
+        match &slots[index] {
+            Some((entry, value)) if entry.matches(key) => {
+                self.hits.fetch_add(1, Ordering::Relaxed);
+                Some(value.clone())
+            }
+            _ => {
+                self.misses.fetch_add(1, Ordering::Relaxed);
+                None
+            }
+        }
+    }
+
+    /// Stores the result for the key, replacing the result in its slot
+    fn insert(&self, key: Key, value: V) {
+        let mut slots = self.slots.lock().unwrap();
+        let index = (key.hash % slots.len() as u64) as usize;
+        slots[index] = Some((Entry::new(&key), value));
+    }
+
+    /// Returns the statistics of the table
+    fn stats(&self) -> CacheStats {
+        CacheStats {
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+            entries: self.slots.lock().unwrap().iter().flatten().count(),
+        }
+    }
+
+    /// Removes every result and resets the statistics
+    fn clear(&self) {
+        for slot in self.slots.lock().unwrap().iter_mut() {
+            *slot = None;
+        }
+        self.hits.store(0, Ordering::Relaxed);
+        self.misses.store(0, Ordering::Relaxed);
+    }
+}
+
+/// ComputedTable memoizes the results of is_tautology and complement
+/// for the sub-covers met during the recursion
+///
+/// Each kind of result is stored in its own bounded table. The tables
+/// can be shared by the tasks of the parallel recursion
+pub struct ComputedTable {
+    tautology: Table<bool>,
+    complement: Table<CubeList>,
+}
+
+impl Default for ComputedTable {
+    fn default() -> Self {
+        ComputedTable::new()
+    }
+}
+
+impl ComputedTable {
+    /// Returns a new empty ComputedTable with DEFAULT_CAPACITY entries per table
+    pub fn new() -> Self {
+        ComputedTable::with_capacity(DEFAULT_CAPACITY)
+    }
+
+    /// Returns a new empty ComputedTable that holds at most capacity
+    /// results of each kind
+    pub fn with_capacity(capacity: usize) -> Self {
+        ComputedTable {
+            tautology: Table::new(capacity),
+            complement: Table::new(capacity),
+        }
+    }
+
+    /// Returns the stored tautology result of a function
+    pub(crate) fn get_tautology(&self, key: &Key) -> Option<bool> {
+        self.tautology.get(key)
+    }
+
+    /// Stores the tautology result of a function
+    pub(crate) fn insert_tautology(&self, key: Key, tautology: bool) {
+        self.tautology.insert(key, tautology);
+    }
+
+    /// Returns the stored complement of a function
+    pub(crate) fn get_complement(&self, key: &Key) -> Option<CubeList> {
+        self.complement.get(key)
+    }
+
+    /// Stores the complement of a function
+    pub(crate) fn insert_complement(&self, key: Key, complement: CubeList) {
+        self.complement.insert(key, complement);
+    }
+
+    /// Returns the statistics of the tautology results
+    pub fn tautology_stats(&self) -> CacheStats {
+        self.tautology.stats()
+    }
+
+    /// Returns the statistics of the complement results
+    pub fn complement_stats(&self) -> CacheStats {
+        self.complement.stats()
+    }
+
+    /// Removes every stored result and resets the statistics
+    pub fn clear(&self) {
+        self.tautology.clear();
+        self.complement.clear();
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::bool_assert_comparison)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn key() {
+        let cubelist_1 = CubeList::from(vec![vec![1, 0], vec![0, -1], vec![1, 0]]);
+        let cubelist_2 = CubeList::from(vec![vec![0, -1], vec![1, 0]]);
+        let entry_1 = Entry::new(&Key::new(&cubelist_1));
+        assert_eq!(entry_1.matches(&Key::new(&cubelist_2)), true);
+        assert_eq!(entry_1.hash, cubelist_2.canonical_hash());
+        assert_eq!(entry_1.cubes, cubelist_2.canonical_cubes());
+        let cubelist_3 = CubeList::from(vec![vec![0, -1], vec![1, 0, 0]]);
+        assert_eq!(entry_1.matches(&Key::new(&cubelist_3)), false);
+    }
+
+    #[test]
+    fn table() {
+        let table = ComputedTable::with_capacity(4);
+        let cubelist = CubeList::from(vec![vec![1, 0], vec![0, -1]]);
+        assert_eq!(table.get_tautology(&Key::new(&cubelist)), None);
+        table.insert_tautology(Key::new(&cubelist), false);
+        assert_eq!(table.get_tautology(&Key::new(&cubelist)), Some(false));
+        assert_eq!(
+            table.tautology_stats(),
+            CacheStats {
+                hits: 1,
+                misses: 1,
+                entries: 1
+            }
+        );
+        assert_eq!(table.complement_stats(), CacheStats::default());
+
+        // The table holds at most 4 results
+        for i in 1..=8 {
+            table.insert_complement(Key::new(&CubeList::new(i)), CubeList::new(i));
+        }
+        assert_eq!(table.complement_stats().entries <= 4, true);
+        table.clear();
+        assert_eq!(table.tautology_stats(), CacheStats::default());
+    }
+}
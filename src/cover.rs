@@ -0,0 +1,233 @@
+/// This function solves the unate covering problem. It returns a minimum
+/// number of columns such that every row has at least one of its columns
+/// selected, along with a boolean value that is true if the result is
+/// proven to be a minimum
+///
+/// # Arguments
+/// * rows - for each row, the columns that cover the row
+/// * node_limit - the maximum number of branch and bound nodes to explore.
+///   When the limit is reached the best cover found so far is returned
+///
+/// Note: This function expects every row to have at least one column
+pub fn minimum_cover(rows: &[Vec<usize>], node_limit: usize) -> (Vec<usize>, bool) {
+    let mut rows: Vec<Vec<usize>> = rows.to_vec();
+    for row in &mut rows {
+        row.sort_unstable();
+        row.dedup();
+    }
+    let mut solver = Solver {
+        best: greedy_cover(&rows),
+        nodes: 0,
+        node_limit,
+        optimal: true,
+    };
+    solver.search(rows, vec![]);
+    (solver.best, solver.optimal)
+}
+
+/// Branch and bound state of the covering problem
+struct Solver {
+    best: Vec<usize>,
+    nodes: usize,
+    node_limit: usize,
+    optimal: bool,
+}
+
+impl Solver {
+    /// Explores the covering problem left after selecting the columns in chosen
+    fn search(&mut self, mut rows: Vec<Vec<usize>>, mut chosen: Vec<usize>) {
+        self.nodes += 1;
+        if self.nodes > self.node_limit {
+            self.optimal = false;
+            return;
+        }
+        reduce(&mut rows, &mut chosen);
+        if rows.iter().any(|row| row.is_empty()) {
+            return;
+        }
+        if rows.is_empty() {
+            if chosen.len() < self.best.len() {
+                self.best = chosen;
+            }
+            return;
+        }
+        if chosen.len() + independent_rows(&rows) >= self.best.len() {
+            return;
+        }
+        // Branch on every column of the row with the fewest columns
+        let row = rows.iter().min_by_key(|row| row.len()).unwrap().clone();
+        for column in row {
+            let remaining: Vec<Vec<usize>> = rows
+                .iter()
+                .filter(|row| !row.contains(&column))
+                .cloned()
+                .collect();
+            let mut selected = chosen.clone();
+            selected.push(column);
+            self.search(remaining, selected);
+        }
+    }
+}
+
+/// Simplifies the covering problem by repeatedly selecting essential columns
+/// and removing dominated rows and columns
+fn reduce(rows: &mut Vec<Vec<usize>>, chosen: &mut Vec<usize>) {
+    loop {
+        // A row with only one column makes that column essential
+        if let Some(row) = rows.iter().find(|row| row.len() == 1) {
+            let column = row[0];
+            chosen.push(column);
+            rows.retain(|row| !row.contains(&column));
+            continue;
+        }
+
+        // A row containing all the columns of another row is always covered
+        let num_rows = rows.len();
+        rows.sort();
+        rows.dedup();
+        let mut dominated = vec![false; rows.len()];
+        for i in 0..rows.len() {
+            for j in 0..rows.len() {
+                if i != j && !dominated[j] && is_subset(&rows[j], &rows[i]) {
+                    dominated[i] = true;
+                    break;
+                }
+            }
+        }
+        let mut i = 0;
+        rows.retain(|_| {
+            i += 1;
+            !dominated[i - 1]
+        });
+
+        // A column covering a subset of the rows of another column is never needed
+        let mut columns: Vec<(usize, Vec<usize>)> = vec![];
+        for (i, row) in rows.iter().enumerate() {
+            for column in row {
+                match columns.iter_mut().find(|(c, _)| c == column) {
+                    Some((_, covered)) => covered.push(i),
+                    None => columns.push((*column, vec![i])),
+                }
+            }
+        }
+        let mut removed = vec![];
+        for (column, covered) in &columns {
+            let is_dominated = columns.iter().any(|(other, other_covered)| {
+                other != column
+                    && !removed.contains(other)
+                    && is_subset(covered, other_covered)
+                    && (covered != other_covered || other < column)
+            });
+            if is_dominated {
+                removed.push(*column);
+            }
+        }
+        for row in rows.iter_mut() {
+            row.retain(|column| !removed.contains(column));
+        }
+
+        if removed.is_empty() && rows.len() == num_rows {
+            break;
+        }
+    }
+}
+
+/// Returns a lower bound on the number of columns needed, which is the
+/// size of a set of rows that do not share any column
+fn independent_rows(rows: &[Vec<usize>]) -> usize {
+    let mut sorted: Vec<&Vec<usize>> = rows.iter().collect();
+    sorted.sort_by_key(|row| row.len());
+    let mut used: Vec<usize> = vec![];
+    let mut count = 0;
+    for row in sorted {
+        if row.iter().all(|column| !used.contains(column)) {
+            used.extend(row.iter());
+            count += 1;
+        }
+    }
+    count
+}
+
+/// Returns a cover obtained by always selecting the column
+/// that covers the most rows
+fn greedy_cover(rows: &[Vec<usize>]) -> Vec<usize> {
+    let mut rows: Vec<&Vec<usize>> = rows.iter().filter(|row| !row.is_empty()).collect();
+    let mut chosen = vec![];
+    while !rows.is_empty() {
+        let mut best_column = rows[0][0];
+        let mut best_count = 0;
+        for row in &rows {
+            for column in row.iter() {
+                let count = rows.iter().filter(|row| row.contains(column)).count();
+                if count > best_count {
+                    best_count = count;
+                    best_column = *column;
+                }
+            }
+        }
+        chosen.push(best_column);
+        rows.retain(|row| !row.contains(&best_column));
+    }
+    chosen
+}
+
+/// Returns true if every element of the sorted vector a is present in the sorted vector b
+fn is_subset(a: &[usize], b: &[usize]) -> bool {
+    let mut j = 0;
+    for value in a {
+        while j < b.len() && b[j] < *value {
+            j += 1;
+        }
+        if j == b.len() || b[j] != *value {
+            return false;
+        }
+        j += 1;
+    }
+    true
+}
+
+#[cfg(test)]
+#[allow(clippy::bool_assert_comparison)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn subset() {
+        assert_eq!(is_subset(&[1, 3], &[1, 2, 3]), true);
+        assert_eq!(is_subset(&[1, 4], &[1, 2, 3]), false);
+        assert_eq!(is_subset(&[], &[1]), true);
+    }
+
+    #[test]
+    fn essential_columns() {
+        let rows = vec![vec![0], vec![0, 1], vec![2], vec![1, 2]];
+        let (mut cover, optimal) = minimum_cover(&rows, 1000);
+        cover.sort_unstable();
+        assert_eq!(cover, vec![0, 2]);
+        assert_eq!(optimal, true);
+    }
+
+    #[test]
+    fn cyclic_covering() {
+        // Each row is covered by two neighbouring columns of a cycle of 5
+        // columns, so at least 3 columns are needed. The greedy cover of
+        // this problem is not a minimum
+        let rows = vec![vec![0, 1], vec![1, 2], vec![2, 3], vec![3, 4], vec![4, 0]];
+        let (cover, optimal) = minimum_cover(&rows, 1000);
+        assert_eq!(cover.len(), 3);
+        assert_eq!(optimal, true);
+        for row in &rows {
+            assert_eq!(row.iter().any(|column| cover.contains(column)), true);
+        }
+    }
+
+    #[test]
+    fn node_limit() {
+        let rows = vec![vec![0, 1], vec![1, 2], vec![2, 3], vec![3, 4], vec![4, 0]];
+        let (cover, optimal) = minimum_cover(&rows, 0);
+        assert_eq!(optimal, false);
+        for row in &rows {
+            assert_eq!(row.iter().any(|column| cover.contains(column)), true);
+        }
+    }
+}
@@ -1,7 +1,14 @@
 use super::cubelist::CubeList;
+use super::error::UrpError;
+
+/// The largest number of variables of a Cube read from a file or generated
+/// at random. Each Cube holds a Literal for every variable, so larger
+/// numbers are rejected before any Cube is built
+pub const MAX_NUM_VAR: usize = 1 << 16;
 
 /// Literal represents the state of a variable in a (product term) Cube
-#[derive(PartialEq, Debug, Clone)]
+#[derive(PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Literal {
     /// The varibale is present as a positive literal
     Positive,
@@ -13,7 +20,15 @@ pub enum Literal {
 
 /// Cube represents a product term and can contain a number of variables
 /// It is vector of Literals and stores the status of each variable in the product term
-#[derive(PartialEq, Debug, Clone)]
+///
+/// With the serde feature, a Cube is serialized as a string in the
+/// format of to_pla_string, e.g. "1-0"
+#[derive(PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Clone)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(try_from = "String", into = "String")
+)]
 pub struct Cube(Vec<Literal>);
 
 impl Cube {
@@ -28,15 +43,20 @@ impl Cube {
         self.0.len()
     }
 
+    /// Returns true if the Cube has no variables
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
     /// Returns the state of the variable in the Cube
     ///
     /// # Arguments
     /// * var_num - variable, whose value is needed
-    pub fn get_literal(&self, var_num: usize) -> Result<Literal, String> {
+    pub fn get_literal(&self, var_num: usize) -> Result<Literal, UrpError> {
         if var_num <= self.len() && var_num != 0 {
             Ok(self.0[var_num - 1].clone())
         } else {
-            Err(format!("variable {} not present", var_num))
+            Err(UrpError::InvalidVariable(var_num))
         }
     }
 
@@ -54,6 +74,114 @@ impl Cube {
         }
     }
 
+    /// Returns the Cube as a string with one character for each variable
+    ///
+    /// A positive literal is written as 1, a negative literal as 0
+    /// and a Dontcare as -. For example x1 x3' is written as "1-0"
+    pub fn to_pla_string(&self) -> String {
+        self.0
+            .iter()
+            .map(|value| match value {
+                Literal::Positive => '1',
+                Literal::Negative => '0',
+                Literal::Dontcare => '-',
+            })
+            .collect()
+    }
+
+    /// Parses a Cube written in the format of to_pla_string
+    pub fn from_pla_str(string: &str) -> Result<Cube, UrpError> {
+        if string.chars().nth(MAX_NUM_VAR).is_some() {
+            return Err(UrpError::Parse {
+                source: format!("cube {}", string),
+                line: 1,
+                column: MAX_NUM_VAR + 1,
+                message: format!("Expected at most {} inputs", MAX_NUM_VAR),
+            });
+        }
+        let mut cube = Cube::new(string.chars().count());
+        for (i, value) in string.chars().enumerate() {
+            cube.0[i] = match value {
+                '1' => Literal::Positive,
+                '0' => Literal::Negative,
+                '-' => Literal::Dontcare,
+                _ => {
+                    return Err(UrpError::Parse {
+                        source: format!("cube {}", string),
+                        line: 1,
+                        column: i + 1,
+                        message: format!("Expected 0, 1 or -, found {}", value),
+                    })
+                }
+            };
+        }
+        Ok(cube)
+    }
+
+    /// Returns the number of variables that are not Dontcare in the Cube
+    pub fn num_literals(&self) -> usize {
+        self.0
+            .iter()
+            .filter(|value| **value != Literal::Dontcare)
+            .count()
+    }
+
+    /// Returns true if every minterm of cube_x is also present in the Cube
+    pub fn contains(&self, cube_x: &Cube) -> bool {
+        self.0
+            .iter()
+            .zip(cube_x.0.iter())
+            .all(|(value, value_x)| *value == Literal::Dontcare || value == value_x)
+    }
+
+    /// Returns all the minterms contained in the Cube
+    ///
+    /// A minterm is a Cube in which no variable is in Dontcare state.
+    /// A Cube with d Dontcare variables contains 2^d minterms
+    pub fn minterms(&self) -> Vec<Cube> {
+        let mut minterms = vec![self.clone()];
+        for i in 0..self.len() {
+            if self.0[i] == Literal::Dontcare {
+                let mut expanded = Vec::with_capacity(minterms.len() * 2);
+                for minterm in minterms {
+                    let mut pos_minterm = minterm.clone();
+                    pos_minterm.0[i] = Literal::Positive;
+                    let mut neg_minterm = minterm;
+                    neg_minterm.0[i] = Literal::Negative;
+                    expanded.push(pos_minterm);
+                    expanded.push(neg_minterm);
+                }
+                minterms = expanded;
+            }
+        }
+        minterms
+    }
+
+    /// Returns a copy of the Cube that has num_var variables
+    ///
+    /// The new variables are in Dontcare state. If num_var is smaller
+    /// than the length of the Cube, the Cube is returned unchanged
+    pub fn extend_vars(&self, num_var: usize) -> Cube {
+        let mut cube = self.clone();
+        if num_var > cube.len() {
+            cube.0.resize(num_var, Literal::Dontcare);
+        }
+        cube
+    }
+
+    /// Returns a copy of the Cube of length num_var in which
+    /// variable i is moved to variable permutation[i - 1]
+    ///
+    /// Note: This function ignores variables that are mapped to
+    /// a wrong variable number, i.e. 0 or greater than num_var
+    pub fn permute(&self, permutation: &[usize], num_var: usize) -> Cube {
+        let mut cube = Cube::new(num_var);
+        for (i, new_var) in permutation.iter().enumerate().take(self.len()) {
+            cube.set_literal(*new_var, self.0[i].clone());
+        }
+        cube
+    }
+
     /// Returns the complement of the Cube as a CubeList
     pub fn complement(&self) -> CubeList {
         let mut cubelist = CubeList::new(self.0.len());
@@ -69,6 +197,33 @@ impl Cube {
         cubelist
     }
 
+    /// Returns the Cube as a product of literals, e.g. "a b c'"
+    ///
+    /// The variables are named a, b, c and so on when the Cube has at most
+    /// 26 variables, and x1, x2, x3 and so on otherwise. A Cube without
+    /// any literal is written as "1"
+    pub fn to_sop_string(&self) -> String {
+        let literals: Vec<String> = self
+            .0
+            .iter()
+            .enumerate()
+            .filter(|(_, value)| **value != Literal::Dontcare)
+            .map(|(i, value)| {
+                let name = var_name(i + 1, self.len());
+                if *value == Literal::Negative {
+                    name + "'"
+                } else {
+                    name
+                }
+            })
+            .collect();
+        if literals.is_empty() {
+            "1".to_string()
+        } else {
+            literals.join(" ")
+        }
+    }
+
     /// Returns a cube that contains only one literal
     pub fn get_var_cube(num_var: usize, var_num: usize, positive: bool) -> Self {
         let mut cube = Cube::new(num_var);
@@ -88,9 +243,9 @@ impl Cube {
         for i in 1..=self.len() {
             if self.get_literal(i).unwrap() == Literal::Dontcare {
                 result.set_literal(i, cube_x.get_literal(i).unwrap());
-            } else if cube_x.get_literal(i).unwrap() == Literal::Dontcare {
-                result.set_literal(i, self.get_literal(i).unwrap());
-            } else if self.get_literal(i).unwrap() == cube_x.get_literal(i).unwrap() {
+            } else if cube_x.get_literal(i).unwrap() == Literal::Dontcare
+                || self.get_literal(i).unwrap() == cube_x.get_literal(i).unwrap()
+            {
                 result.set_literal(i, self.get_literal(i).unwrap());
             } else {
                 return None;
@@ -100,6 +255,15 @@ impl Cube {
     }
 }
 
+/// Returns the name of a variable as used by to_sop_string
+pub fn var_name(var_num: usize, num_var: usize) -> String {
+    if num_var <= 26 {
+        ((b'a' + (var_num - 1) as u8) as char).to_string()
+    } else {
+        format!("x{}", var_num)
+    }
+}
+
 /// This implements conversion from a vector of i32 to a Cube
 ///
 /// A positive value indicates that the corresponding variable is present as a positive literal
@@ -107,15 +271,17 @@ impl Cube {
 /// A zero indicates that the corresponding variable is not present in the Cube
 ///
 /// ```
+/// use urp::Cube;
+///
 /// let cube = Cube::from(vec![1, 0, -1]);
 /// ```
 impl From<Vec<i32>> for Cube {
     fn from(vector: Vec<i32>) -> Self {
         let mut cube = Cube::new(vector.len());
-        for i in 0..vector.len() {
-            if vector[i] > 0 {
+        for (i, value) in vector.iter().enumerate() {
+            if *value > 0 {
                 cube.set_literal(i + 1, Literal::Positive);
-            } else if vector[i] < 0 {
+            } else if *value < 0 {
                 cube.set_literal(i + 1, Literal::Negative);
             } else {
                 cube.set_literal(i + 1, Literal::Dontcare);
@@ -125,20 +291,52 @@ impl From<Vec<i32>> for Cube {
     }
 }
 
+impl From<Cube> for String {
+    fn from(cube: Cube) -> Self {
+        cube.to_pla_string()
+    }
+}
+
+impl std::convert::TryFrom<String> for Cube {
+    type Error = UrpError;
+
+    fn try_from(string: String) -> Result<Self, Self::Error> {
+        Cube::from_pla_str(&string)
+    }
+}
+
+/// This implements indexing of the Literals of a Cube
+///
+/// Variables are numbered from 1 as in get_literal. Indexing panics
+/// if the variable number is 0 or greater than the length of the Cube
+///
+/// ```
+/// use urp::{Cube, Literal};
+///
+/// let cube = Cube::from(vec![1, 0, -1]);
+/// assert_eq!(cube[3], Literal::Negative);
+/// ```
+impl std::ops::Index<usize> for Cube {
+    type Output = Literal;
+
+    fn index(&self, var_num: usize) -> &Literal {
+        assert!(
+            var_num != 0 && var_num <= self.len(),
+            "variable {} not present",
+            var_num
+        );
+        &self.0[var_num - 1]
+    }
+}
+
 impl std::fmt::Display for Cube {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> Result<(), std::fmt::Error> {
-        let mut non_dont_care_count = 0;
-        for value in &self.0 {
-            if *value != Literal::Dontcare {
-                non_dont_care_count += 1;
-            }
-        }
-        write!(f, "{} ", non_dont_care_count);
+        write!(f, "{} ", self.num_literals())?;
         for i in 0..self.0.len() {
             if self.0[i] == Literal::Positive {
-                write!(f, "{} ", (i + 1));
+                write!(f, "{} ", (i + 1))?;
             } else if self.0[i] == Literal::Negative {
-                write!(f, "{} ", -((i + 1) as i32));
+                write!(f, "{} ", -((i + 1) as i32))?;
             }
         }
         Ok(())
@@ -146,8 +344,11 @@ impl std::fmt::Display for Cube {
 }
 
 #[cfg(test)]
+#[allow(clippy::bool_assert_comparison)]
 mod test {
     use super::*;
+    use crate::oracle::{self, cube_value, truth_table, MAX_VARS};
+    use proptest::prelude::*;
 
     #[test]
     fn create_new_cube() {
@@ -176,15 +377,92 @@ mod test {
         assert_eq!(cube.get_literal(3).unwrap(), Literal::Positive);
     }
 
+    #[test]
+    fn sop_string() {
+        assert_eq!(Cube::from(vec![1, 1, -1, 0]).to_sop_string(), "a b c'");
+        assert_eq!(Cube::new(3).to_sop_string(), "1");
+        let mut cube = Cube::new(30);
+        cube.set_literal(27, Literal::Negative);
+        assert_eq!(cube.to_sop_string(), "x27'");
+    }
+
+    #[test]
+    fn pla_string() {
+        let cube = Cube::from(vec![1, 0, -1]);
+        assert_eq!(cube.to_pla_string(), "1-0");
+        assert_eq!(Cube::from_pla_str("1-0").unwrap(), cube);
+        assert_eq!(Cube::from_pla_str("").unwrap(), Cube::new(0));
+        assert_eq!(Cube::from_pla_str("1x0").is_err(), true);
+        assert_eq!(Cube::from_pla_str(&"-".repeat(MAX_NUM_VAR)).is_ok(), true);
+        assert_eq!(
+            Cube::from_pla_str(&"-".repeat(MAX_NUM_VAR + 1)).is_err(),
+            true
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde() {
+        let cube = Cube::from(vec![1, 0, -1]);
+        assert_eq!(serde_json::to_string(&cube).unwrap(), "\"1-0\"");
+        assert_eq!(serde_json::from_str::<Cube>("\"1-0\"").unwrap(), cube);
+        assert_eq!(serde_json::from_str::<Cube>("\"1-2\"").is_err(), true);
+    }
+
+    #[test]
+    fn index() {
+        let cube = Cube::from(vec![0, -1, 1]);
+        assert_eq!(cube[1], Literal::Dontcare);
+        assert_eq!(cube[2], Literal::Negative);
+        assert_eq!(cube[3], Literal::Positive);
+    }
+
+    #[test]
+    #[should_panic]
+    fn index_out_of_range() {
+        let _ = Cube::new(3)[0];
+    }
+
     #[test]
     fn cube_equal() {
         let mut cube_x = Cube::from(vec![1, 0, -1]);
-        let mut cube_y = Cube::from(vec![1, 0, -1]);
+        let cube_y = Cube::from(vec![1, 0, -1]);
         assert_eq!(cube_x, cube_y);
         cube_x.set_literal(2, Literal::Negative);
         assert_ne!(cube_x, cube_y);
     }
 
+    #[test]
+    fn contains() {
+        let cube_x = Cube::from(vec![1, 0, 0]);
+        let cube_y = Cube::from(vec![1, 0, -1]);
+        assert_eq!(cube_x.num_literals(), 1);
+        assert_eq!(cube_y.num_literals(), 2);
+        assert_eq!(cube_x.contains(&cube_y), true);
+        assert_eq!(cube_y.contains(&cube_x), false);
+        assert_eq!(cube_x.contains(&Cube::from(vec![-1, 0, -1])), false);
+    }
+
+    #[test]
+    fn minterms() {
+        let minterms = Cube::from(vec![1, 0, 0]).minterms();
+        assert_eq!(minterms.len(), 4);
+        assert_eq!(minterms.contains(&Cube::from(vec![1, -1, 1])), true);
+        assert_eq!(
+            Cube::from(vec![1, -1]).minterms(),
+            vec![Cube::from(vec![1, -1])]
+        );
+    }
+
+    #[test]
+    fn extend_and_permute() {
+        let cube = Cube::from(vec![1, 0, -1]);
+        assert_eq!(cube.extend_vars(5), Cube::from(vec![1, 0, -1, 0, 0]));
+        assert_eq!(cube.extend_vars(2), cube);
+        assert_eq!(cube.permute(&[3, 1, 2], 3), Cube::from(vec![0, -1, 1]));
+        assert_eq!(cube.permute(&[4, 2, 1], 4), Cube::from(vec![-1, 0, 0, 1]));
+    }
+
     #[test]
     fn complement() {
         // Complement of 1 is 0 and so it returns an empty CubeList
@@ -209,13 +487,45 @@ mod test {
     #[test]
     fn and() {
         // Normal AND of two Cubes
-        let mut cube_x = Cube::from(vec![1, 0, 0]);
+        let cube_x = Cube::from(vec![1, 0, 0]);
         let mut cube_y = Cube::from(vec![0, 0, -1]);
-        let mut cube_z = Cube::from(vec![1, 0, -1]);
+        let cube_z = Cube::from(vec![1, 0, -1]);
         assert_eq!(cube_x.and(&cube_y).unwrap(), cube_z);
 
         // AND of complementing literals
         cube_y.set_literal(3, Literal::Positive);
         assert_eq!(cube_z.and(&cube_y).is_none(), true);
     }
+
+    /// Returns a strategy of pairs of Cubes with the same number of variables
+    fn cube_pair() -> impl Strategy<Value = (Cube, Cube)> {
+        (1..=MAX_VARS).prop_flat_map(|num_var| (oracle::cube(num_var), oracle::cube(num_var)))
+    }
+
+    proptest! {
+        #[test]
+        fn and_matches_truth_table((cube_x, cube_y) in cube_pair()) {
+            let and = cube_x.and(&cube_y);
+            for minterm in 0..1 << cube_x.len() {
+                let expected = cube_value(&cube_x, minterm) && cube_value(&cube_y, minterm);
+                let value = and.as_ref().is_some_and(|and| cube_value(and, minterm));
+                prop_assert_eq!(value, expected, "minterm {}", minterm);
+            }
+        }
+
+        #[test]
+        fn complement_matches_truth_table(cube in (1..=MAX_VARS).prop_flat_map(oracle::cube)) {
+            let complement = truth_table(&cube.complement());
+            for (minterm, value) in complement.into_iter().enumerate() {
+                prop_assert_eq!(value, !cube_value(&cube, minterm), "minterm {}", minterm);
+            }
+        }
+
+        #[test]
+        fn contains_matches_truth_table((cube_x, cube_y) in cube_pair()) {
+            let expected = (0..1 << cube_x.len())
+                .all(|minterm| !cube_value(&cube_y, minterm) || cube_value(&cube_x, minterm));
+            prop_assert_eq!(cube_x.contains(&cube_y), expected);
+        }
+    }
 }
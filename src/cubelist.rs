@@ -1,14 +1,162 @@
+use super::cache::{ComputedTable, Key};
+use super::cover;
 use super::cube::{Cube, Literal};
-use std::fs::{File, OpenOptions};
-use std::io::{BufRead, BufReader, Write};
+use super::error::UrpError;
+use super::options::{SplitStrategy, UrpOptions};
+use super::pla;
+use super::rng::SplitMix64;
+use super::rules::Rule;
+use super::stream::CubeReader;
+use super::trace::{Operation, Step};
+use std::collections::BTreeSet;
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufReader, Write};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Instant;
+
+/// The largest number of minterms for which minimize_exact builds the prime table
+const MAX_EXACT_MINTERMS: usize = 1 << 14;
+
+/// The number of branch and bound nodes after which minimize_exact gives up
+/// proving that its result is a minimum
+const MAX_EXACT_NODES: usize = 100_000;
+
+/// The number of levels of the URP recursion whose cofactors are
+/// handled in parallel by complement_parallel and is_tautology_parallel
+const PARALLEL_DEPTH: usize = 10;
+
+/// Cubelists with fewer Cubes are handled sequentially, as splitting
+/// them into tasks costs more than it saves
+const PARALLEL_MIN_CUBES: usize = 16;
+
+/// Run holds the options of a complement or a tautology check,
+/// with the deadline computed when it started
+struct Run<'a> {
+    options: &'a UrpOptions<'a>,
+    deadline: Option<Instant>,
+}
+
+impl<'a> Run<'a> {
+    /// Returns true if the cofactors of a cover with the given number of
+    /// Cubes at the given depth of the recursion are handled in parallel
+    fn parallel(&self, depth: usize, cubes: usize) -> bool {
+        self.options.parallel && depth < PARALLEL_DEPTH && cubes >= PARALLEL_MIN_CUBES
+    }
+
+    /// Records a node for a cover met by the recursion and returns its
+    /// number, if the recursion is traced
+    fn enter(
+        &self,
+        parent: Option<usize>,
+        operation: Operation,
+        depth: usize,
+        cubes: usize,
+    ) -> Option<usize> {
+        let trace = self.options.trace?;
+        Some(trace.enter(parent, operation, depth, cubes))
+    }
+
+    /// Records the step that handled the cover of a traced node
+    fn step(&self, node: Option<usize>, step: Step) {
+        if let (Some(trace), Some(node)) = (self.options.trace, node) {
+            trace.step(node, step);
+        }
+    }
+
+    /// Counts a rule applied by is_tautology and records it as the step
+    /// of a traced node
+    fn apply(&self, node: Option<usize>, rule: Rule) {
+        self.options.count(rule);
+        self.step(node, Step::Rule(rule));
+    }
+
+    /// Records the variable a traced node was split on
+    fn split(&self, node: Option<usize>, var_num: usize) {
+        if let (Some(trace), Some(node)) = (self.options.trace, node) {
+            trace.split(node, var_num);
+        }
+    }
+
+    /// Records the time spent on a traced node
+    fn leave(&self, node: Option<usize>) {
+        if let (Some(trace), Some(node)) = (self.options.trace, node) {
+            trace.leave(node);
+        }
+    }
+}
+
+/// Opens a file to be read one line at a time
+fn open(file: &str) -> Result<BufReader<File>, UrpError> {
+    let reader = File::open(file).map_err(|error| UrpError::Io {
+        path: file.to_string(),
+        error,
+    })?;
+    Ok(BufReader::new(reader))
+}
+
+/// Runs a and b, in parallel when the parallel feature is enabled,
+/// and returns their results
+fn join<A, B, RA, RB>(a: A, b: B) -> (RA, RB)
+where
+    A: FnOnce() -> RA + Send,
+    B: FnOnce() -> RB + Send,
+    RA: Send,
+    RB: Send,
+{
+    #[cfg(feature = "parallel")]
+    return rayon::join(a, b);
+    #[cfg(not(feature = "parallel"))]
+    return (a(), b());
+}
 
 /// CubeList represents a Boolean function.
 /// It is a vector of Cubes. Each Cube represents a product term and
 /// the function is obtained by summing (performing logical OR of) all
 /// the product terms
+///
+/// With the serde feature, a CubeList is serialized as its number of
+/// variables and its Cubes, e.g. {"num_var": 3, "cubes": ["1-0", "-1-"]}
 #[derive(Debug, Clone)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(try_from = "CubeListData", into = "CubeListData")
+)]
 pub struct CubeList(Vec<Cube>, usize);
 
+/// The serialized form of a CubeList
+#[cfg(feature = "serde")]
+#[derive(serde::Serialize, serde::Deserialize)]
+struct CubeListData {
+    num_var: usize,
+    cubes: Vec<Cube>,
+}
+
+#[cfg(feature = "serde")]
+impl From<CubeList> for CubeListData {
+    fn from(cubelist: CubeList) -> Self {
+        CubeListData {
+            num_var: cubelist.1,
+            cubes: cubelist.0,
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl std::convert::TryFrom<CubeListData> for CubeList {
+    type Error = UrpError;
+
+    fn try_from(data: CubeListData) -> Result<Self, Self::Error> {
+        if let Some(cube) = data.cubes.iter().find(|cube| cube.len() != data.num_var) {
+            return Err(UrpError::VarCountMismatch {
+                expected: data.num_var,
+                found: cube.len(),
+            });
+        }
+        Ok(CubeList(data.cubes, data.num_var))
+    }
+}
+
 impl CubeList {
     /// This function returns the number of Cube or Product terms present
     /// in the CubeList or Boolean function
@@ -16,11 +164,67 @@ impl CubeList {
         self.0.len()
     }
 
+    /// This function returns true if the CubeList has no Cubes,
+    /// i.e. if the Boolean function is always 0
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
     /// Returns an empty CubeList
     pub fn new(num_var: usize) -> Self {
         CubeList(vec![], num_var)
     }
 
+    /// Returns the number of variables of the boolean function
+    pub fn num_var(&self) -> usize {
+        self.1
+    }
+
+    /// Returns a copy of the CubeList that has num_var variables
+    ///
+    /// The new variables are not present in any of the Cubes. If num_var
+    /// is smaller than the current number of variables, the CubeList is
+    /// returned unchanged
+    pub fn extend_vars(&self, num_var: usize) -> CubeList {
+        if num_var <= self.1 {
+            return self.clone();
+        }
+        let mut cubelist = CubeList::new(num_var);
+        for cube in &self.0 {
+            cubelist.add_cube(cube.extend_vars(num_var));
+        }
+        cubelist
+    }
+
+    /// Returns a copy of the CubeList with the variables renamed
+    ///
+    /// # Arguments
+    /// * permutation - permutation[i - 1] is the new variable number of variable i.
+    ///   It must contain one distinct non zero entry for every variable. The
+    ///   number of variables of the result is the largest of the current number
+    ///   of variables and the entries of permutation
+    pub fn permute(&self, permutation: &[usize]) -> Result<CubeList, UrpError> {
+        if permutation.len() != self.1 {
+            return Err(UrpError::VarCountMismatch {
+                expected: self.1,
+                found: permutation.len(),
+            });
+        }
+        let mut seen = Vec::new();
+        for new_var in permutation {
+            if *new_var == 0 || seen.contains(new_var) {
+                return Err(UrpError::InvalidVariable(*new_var));
+            }
+            seen.push(*new_var);
+        }
+        let num_var = permutation.iter().cloned().fold(self.1, usize::max);
+        let mut cubelist = CubeList::new(num_var);
+        for cube in &self.0 {
+            cubelist.add_cube(cube.permute(permutation, num_var));
+        }
+        Ok(cubelist)
+    }
+
     /// This function reads the cubelist from the PCN file
     /// that contains the information about the cubes in
     /// the following format
@@ -28,55 +232,78 @@ impl CubeList {
     /// * The first line contains the number of variable
     /// * The second line contains N, the number of cubes in the cubelist
     /// * Each of the N following lines shows which variables are present
-    /// in each cube. First number is the number of Non Dont care variables
-    /// in th cube followed by the variable numbers. A positive number indicates
-    /// that it is present as a positive literal and a negative number indicates
-    /// that it is present as a negative literal
-    pub fn read_from_file(file: &str) -> Self {
-        let mut reader = BufReader::new(File::open(file).expect("File could not be read"));
-        let mut buffer = String::new();
-        reader.read_line(&mut buffer);
-        let num_var = buffer
-            .trim()
-            .parse::<u32>()
-            .expect("Number of variables is invalid");
-        buffer.clear();
-        reader.read_line(&mut buffer);
-        let num_cubes = buffer
-            .trim()
-            .parse::<u32>()
-            .expect("Number of cubes is invalid");
-        let mut vectors = Vec::new();
-        for _ in 0..num_cubes {
-            let mut cube_vector: Vec<i32> = vec![0; num_var as usize];
-            buffer.clear();
-            reader.read_line(&mut buffer);
-            for var in buffer.trim().split_whitespace().skip(1) {
-                let var_num = var
-                    .trim()
-                    .parse::<i32>()
-                    .expect("Expected an integer value");
-                if var_num < 0 {
-                    cube_vector[(var_num.abs() - 1) as usize] = -1;
-                } else if var_num > 0 {
-                    cube_vector[(var_num - 1) as usize] = 1;
-                }
-            }
-            vectors.push(cube_vector);
-        }
-        CubeList::from(vectors)
+    ///   in each cube. First number is the number of Non Dont care variables
+    ///   in th cube followed by the variable numbers. A positive number indicates
+    ///   that it is present as a positive literal and a negative number indicates
+    ///   that it is present as a negative literal
+    ///
+    /// If the file has more than one problem, the first one is returned.
+    /// Use pcn::parse to get all of them. The file is read one line at a
+    /// time with a CubeReader
+    pub fn read_from_file(file: &str) -> Result<Self, UrpError> {
+        CubeReader::pcn(file, open(file)?)?.into_cubelist()
     }
 
     /// Writes the boolean function represented by the CubeList into
     /// a file in the format specified in the read_from_file function
-    pub fn write_to_file(&self, file: &str) {
+    pub fn write_to_file(&self, file: &str) -> Result<(), UrpError> {
+        let io_error = |error| UrpError::Io {
+            path: file.to_string(),
+            error,
+        };
         let mut output_file = OpenOptions::new()
             .create(true)
             .write(true)
             .truncate(true)
             .open(file)
-            .expect("Cannot open file for writing");
-        write!(output_file, "{}", self);
+            .map_err(io_error)?;
+        write!(output_file, "{}", self).map_err(io_error)
+    }
+
+    /// This function reads the cubelist from a single output PLA file
+    ///
+    /// The format is described in pla::parse. If the file has more than
+    /// one problem, the first one is returned
+    pub fn read_pla(file: &str) -> Result<Self, UrpError> {
+        CubeReader::pla(file, open(file)?)?.into_cubelist()
+    }
+
+    /// Writes the boolean function represented by the CubeList
+    /// into a single output PLA file
+    pub fn write_pla(&self, file: &str) -> Result<(), UrpError> {
+        fs::write(file, pla::to_string(self)).map_err(|error| UrpError::Io {
+            path: file.to_string(),
+            error,
+        })
+    }
+
+    /// This function reads the cubelist from a JSON file in the
+    /// format described for the serde feature of CubeList
+    #[cfg(feature = "serde")]
+    pub fn read_json(file: &str) -> Result<Self, UrpError> {
+        let contents = fs::read_to_string(file).map_err(|error| UrpError::Io {
+            path: file.to_string(),
+            error,
+        })?;
+        serde_json::from_str(&contents).map_err(|error| UrpError::Parse {
+            source: file.to_string(),
+            line: error.line(),
+            column: error.column(),
+            message: error.to_string(),
+        })
+    }
+
+    /// Writes the boolean function represented by the CubeList
+    /// into a JSON file in the format read by read_json
+    #[cfg(feature = "serde")]
+    pub fn write_json(&self, file: &str) -> Result<(), UrpError> {
+        let io_error = |error| UrpError::Io {
+            path: file.to_string(),
+            error,
+        };
+        let contents =
+            serde_json::to_string_pretty(self).map_err(|error| io_error(error.into()))?;
+        fs::write(file, contents + "\n").map_err(io_error)
     }
 
     /// This function adds a Cube to the CubeList
@@ -84,6 +311,11 @@ impl CubeList {
         self.0.push(cube_x);
     }
 
+    /// Returns an iterator over the Cubes of the CubeList
+    pub fn iter(&self) -> std::slice::Iter<'_, Cube> {
+        self.0.iter()
+    }
+
     /// This function checks if the cubelist contains a particular cube
     pub fn contains_cube(&self, cube_x: &Cube) -> bool {
         self.0.contains(cube_x)
@@ -91,10 +323,15 @@ impl CubeList {
 
     /// This funcitons performs Logical AND of the boolean function
     /// with another boolean function represented as a CubeList
+    ///
+    /// If the number of variables differ, both functions are first
+    /// extended to the larger number of variables
     pub fn and(&self, cubelist_x: &CubeList) -> CubeList {
-        let mut result = CubeList::new(self.1);
-        for cube_x in &self.0 {
-            for cube_y in &cubelist_x.0 {
+        let num_var = self.num_var().max(cubelist_x.num_var());
+        let (cubelist_a, cubelist_b) = (self.extend_vars(num_var), cubelist_x.extend_vars(num_var));
+        let mut result = CubeList::new(num_var);
+        for cube_x in &cubelist_a.0 {
+            for cube_y in &cubelist_b.0 {
                 if let Some(new_cube) = cube_x.and(cube_y) {
                     if !result.contains_cube(&new_cube) {
                         result.add_cube(new_cube);
@@ -107,94 +344,203 @@ impl CubeList {
 
     /// This funcitons performs Logical OR of the boolean function
     /// with another boolean function represented as a CubeList
+    ///
+    /// If the number of variables differ, both functions are first
+    /// extended to the larger number of variables
     pub fn or(&self, cubelist_x: &CubeList) -> CubeList {
-        let mut result_cubelist: CubeList = self.clone();
-        for cube in &cubelist_x.0 {
-            if !result_cubelist.contains_cube(&cube) {
+        let num_var = self.num_var().max(cubelist_x.num_var());
+        let mut result_cubelist: CubeList = self.extend_vars(num_var);
+        for cube in &cubelist_x.extend_vars(num_var).0 {
+            if !result_cubelist.contains_cube(cube) {
                 result_cubelist.add_cube(cube.clone());
             }
         }
         result_cubelist
     }
 
+    /// This funcitons performs Logical XOR of the boolean function
+    /// with another boolean function represented as a CubeList
+    pub fn xor(&self, cubelist_x: &CubeList) -> CubeList {
+        self.and(&cubelist_x.complement())
+            .or(&self.complement().and(cubelist_x))
+    }
+
     /// This funcitons returns the complement of the boolean function
     pub fn complement(&self) -> CubeList {
+        // Without limits or a cancellation token the complement cannot fail
+        self.complement_with(&UrpOptions::default()).unwrap()
+    }
+
+    /// This function returns the complement of the function like complement,
+    /// with the cofactors of the first levels of the recursion complemented
+    /// in parallel when the parallel feature is enabled
+    ///
+    /// The result is identical to the one of complement
+    pub fn complement_parallel(&self) -> CubeList {
+        self.complement_with(&UrpOptions {
+            parallel: true,
+            ..UrpOptions::default()
+        })
+        .unwrap()
+    }
+
+    /// This function returns the complement of the function like complement,
+    /// reusing the complements of sub-covers stored in the cache and storing
+    /// the new ones
+    pub fn complement_cached(&self, cache: &ComputedTable) -> CubeList {
+        self.complement_with(&UrpOptions {
+            cache: Some(cache),
+            ..UrpOptions::default()
+        })
+        .unwrap()
+    }
+
+    /// This function returns the complement of the function, computed
+    /// with the given options
+    ///
+    /// Returns a LimitExceeded error if one of the limits of the options
+    /// is exceeded or the operation is cancelled
+    pub fn complement_with(&self, options: &UrpOptions) -> Result<CubeList, UrpError> {
+        let run = Run {
+            options,
+            deadline: options.deadline(),
+        };
+        self.complement_split(0, None, &run)
+    }
+
+    /// Returns the complement of a cover at the given depth of the recursion,
+    /// derived from the cover of the traced node parent
+    fn complement_split(
+        &self,
+        depth: usize,
+        parent: Option<usize>,
+        run: &Run,
+    ) -> Result<CubeList, UrpError> {
+        let node = run.enter(parent, Operation::Complement, depth, self.len());
+        let result = self.complement_step(depth, node, run);
+        run.leave(node);
+        result
+    }
+
+    /// Complements the cover of the traced node with the first step of
+    /// complement_split that applies
+    fn complement_step(
+        &self,
+        depth: usize,
+        node: Option<usize>,
+        run: &Run,
+    ) -> Result<CubeList, UrpError> {
         let mut cubelist = CubeList::new(self.1);
-        if self.len() == 0 {
+        if self.is_empty() {
+            run.step(node, Step::Empty);
             cubelist.add_cube(Cube::from(vec![0; self.1]));
-            cubelist
+            return Ok(cubelist);
         } else if self.contains_cube(&Cube::from(vec![0; self.1])) {
-            cubelist
+            run.step(node, Step::Rule(Rule::DontCareRow));
+            return Ok(cubelist);
         } else if self.len() == 1 {
-            self.0[0].complement()
+            run.step(node, Step::SingleCube);
+            return Ok(self.0[0].complement());
+        }
+        run.options.check(depth, run.deadline)?;
+        let cache = run.options.cache;
+        let key = cache.map(|_| Key::new(self));
+        if let (Some(cache), Some(key)) = (cache, &key) {
+            if let Some(complement) = cache.get_complement(key) {
+                run.step(node, Step::Cached);
+                return Ok(complement);
+            }
+        }
+        let components = self.components();
+        let complement = if components.len() > 1 {
+            // The complement of an OR of functions on disjoint variables
+            // is the AND of their complements
+            run.step(node, Step::Rule(Rule::Components));
+            let mut complement = CubeList::from(vec![vec![0; self.1]]);
+            for component in &components {
+                complement = complement.and(&component.complement_split(depth, node, run)?);
+                run.options.check_cubes(complement.len())?;
+            }
+            complement
         } else {
-            let var_num = self.get_most_unate_var();
+            let var_num = self.split_var(run.options.split);
+            run.step(node, Step::Rule(Rule::Split));
+            run.split(node, var_num);
             let (pos_cubelist, neg_cubelist) = self.cofactor(var_num);
+            let (pos_complement, neg_complement) = if run.parallel(depth, self.len()) {
+                join(
+                    || pos_cubelist.complement_split(depth + 1, node, run),
+                    || neg_cubelist.complement_split(depth + 1, node, run),
+                )
+            } else {
+                (
+                    pos_cubelist.complement_split(depth + 1, node, run),
+                    neg_cubelist.complement_split(depth + 1, node, run),
+                )
+            };
             let mut cubelist_x_pos = CubeList::new(self.1);
             cubelist_x_pos.add_cube(Cube::get_var_cube(self.1, var_num, true));
             let mut cubelist_x_neg = CubeList::new(self.1);
             cubelist_x_neg.add_cube(Cube::get_var_cube(self.1, var_num, false));
-            (cubelist_x_pos.and(&pos_cubelist.complement()))
-                .or(&cubelist_x_neg.and(&neg_cubelist.complement()))
+            (cubelist_x_pos.and(&pos_complement?)).or(&cubelist_x_neg.and(&neg_complement?))
+        };
+        run.options.check_cubes(complement.len())?;
+        if let (Some(cache), Some(key)) = (cache, key) {
+            cache.insert_complement(key, complement.clone());
         }
+        Ok(complement)
     }
 
-    /// Returns a variable most suited to split on
+    /// Returns a variable most suited to split on, chosen by the
+    /// MostBinate strategy of split_var
     pub fn get_most_unate_var(&self) -> usize {
-        let mut pos_count = vec![0; self.1];
-        let mut neg_count = vec![0; self.1];
-        for cube in &self.0 {
-            for i in 1..=self.1 {
-                if cube.get_literal(i).unwrap() == Literal::Positive {
-                    pos_count[i - 1] += 1;
-                } else if cube.get_literal(i).unwrap() == Literal::Negative {
-                    neg_count[i - 1] += 1;
-                }
-            }
-        }
-        let mut unate = true;
-        let mut max_var = 0;
-        let mut max_val = 0;
-        let mut more_than_one = false;
-        for i in 1..=self.1 {
-            if (pos_count[i - 1] == 0 || neg_count[i - 1] == 0) && unate {
-                if max_val < pos_count[i - 1] + neg_count[i - 1] {
-                    max_val = pos_count[i - 1] + neg_count[i - 1];
-                    max_var = i;
-                    more_than_one = false;
-                } else if max_val == pos_count[i - 1] + neg_count[i - 1] {
-                    more_than_one = true;
-                }
-            } else {
-                if unate {
-                    unate = false;
-                    max_val = pos_count[i - 1] + neg_count[i - 1];
-                    max_var = i;
-                    more_than_one = false;
-                } else {
-                    if max_val < pos_count[i - 1] + neg_count[i - 1] {
-                        max_val = pos_count[i - 1] + neg_count[i - 1];
-                        max_var = i;
-                    } else if max_val == pos_count[i - 1] + neg_count[i - 1] {
-                        more_than_one = true;
-                    }
-                }
-            }
-        }
-        if !more_than_one || unate {
-            max_var
+        self.split_var(SplitStrategy::MostBinate)
+    }
+
+    /// This function returns the variable chosen by the strategy to split
+    /// the cover on, or 0 if the cover has no literal
+    pub fn split_var(&self, strategy: SplitStrategy) -> usize {
+        let (pos_count, neg_count) = self.literal_counts();
+        let count = |i: usize| pos_count[i - 1] + neg_count[i - 1];
+        let balance = |i: usize| (pos_count[i - 1] as i64 - neg_count[i - 1] as i64).abs();
+        let binate: Vec<usize> = (1..=self.1)
+            .filter(|i| pos_count[i - 1] != 0 && neg_count[i - 1] != 0)
+            .collect();
+        // The variables to choose from
+        let vars = if binate.is_empty() {
+            (1..=self.1).filter(|i| count(*i) != 0).collect()
         } else {
-            let mut min_bal = std::u32::MAX;
-            let mut min_var = 0;
-            for i in 1..=self.1 {
-                if pos_count[i - 1] != 0 && neg_count[i - 1] != 0 {
-                    if min_bal > (pos_count[i - 1] as i32 - neg_count[i - 1] as i32).abs() as u32 {
-                        min_bal = (pos_count[i - 1] as i32 - neg_count[i - 1] as i32).abs() as u32;
-                        min_var = i;
-                    }
+            binate
+        };
+        // The first variable with the largest key
+        let max_by_key = |key: &dyn Fn(usize) -> (i64, i64)| {
+            vars.iter()
+                .cloned()
+                .fold(None, |best: Option<usize>, i| match best {
+                    Some(best) if key(best) >= key(i) => Some(best),
+                    _ => Some(i),
+                })
+                .unwrap_or(0)
+        };
+        match strategy {
+            SplitStrategy::MostBinate => most_binate_var(&pos_count, &neg_count),
+            SplitStrategy::MostBinateBalanced => max_by_key(&|i| (count(i) as i64, -balance(i))),
+            SplitStrategy::MostBalanced => max_by_key(&|i| (-balance(i), count(i) as i64)),
+            SplitStrategy::FirstBinate => vars.first().cloned().unwrap_or(0),
+            SplitStrategy::MaxLiterals => (1..=self.1)
+                .fold(None, |best: Option<usize>, i| match best {
+                    Some(best) if count(best) >= count(i) => Some(best),
+                    _ if count(i) == 0 => best,
+                    _ => Some(i),
+                })
+                .unwrap_or(0),
+            SplitStrategy::Random { seed } => {
+                if vars.is_empty() {
+                    return 0;
                 }
+                let mut rng = SplitMix64::new(seed ^ self.canonical_hash());
+                vars[rng.below(vars.len())]
             }
-            min_var
         }
     }
 
@@ -224,24 +570,493 @@ impl CubeList {
         (pos_cofactor, neg_cofactor)
     }
 
+    /// This function substitutes the boolean function cubelist_g in place of
+    /// the variable indicated by var_num, i.e. it returns F(x := G)
+    ///
+    /// The result is computed from the Shannon Cofactors of F with respect to x
+    /// as G.F_x + G'.F_x'
+    pub fn compose(&self, var_num: usize, cubelist_g: &CubeList) -> Result<CubeList, UrpError> {
+        if var_num == 0 || var_num > self.1 {
+            return Err(UrpError::InvalidVariable(var_num));
+        }
+        let (pos_cubelist, neg_cubelist) = self.cofactor(var_num);
+        Ok((cubelist_g.and(&pos_cubelist)).or(&cubelist_g.complement().and(&neg_cubelist)))
+    }
+
+    /// This function returns the cofactor of the function with respect to
+    /// the product term cube_x, i.e. the function obtained by setting all
+    /// the literals of cube_x to 1
+    pub fn cube_cofactor(&self, cube_x: &Cube) -> CubeList {
+        let mut cubelist = CubeList::new(self.1);
+        for cube in &self.0 {
+            if cube.and(cube_x).is_some() {
+                let mut new_cube = cube.clone();
+                for i in 1..=self.1 {
+                    if cube_x.get_literal(i).unwrap() != Literal::Dontcare {
+                        new_cube.set_literal(i, Literal::Dontcare);
+                    }
+                }
+                cubelist.add_cube(new_cube);
+            }
+        }
+        cubelist
+    }
+
+    /// This function returns true if every minterm of cube_x is
+    /// covered by the function, i.e. if the cofactor of the function
+    /// with respect to cube_x is a tautology
+    pub fn covers_cube(&self, cube_x: &Cube) -> bool {
+        self.0.iter().any(|cube| cube.contains(cube_x)) || self.cube_cofactor(cube_x).is_tautology()
+    }
+
+    /// This function returns if every minterm of cube_x is covered by the
+    /// function like covers_cube, with the tautology check done with the
+    /// given options
+    pub fn covers_cube_with(&self, cube_x: &Cube, options: &UrpOptions) -> Result<bool, UrpError> {
+        if self.0.iter().any(|cube| cube.contains(cube_x)) {
+            return Ok(true);
+        }
+        self.cube_cofactor(cube_x).is_tautology_with(options)
+    }
+
+    /// This function returns if the function is equivalent to cubelist_x,
+    /// i.e. if each function covers every Cube of the other, with the
+    /// tautology checks done with the given options
+    ///
+    /// If the number of variables differ, both functions are first
+    /// extended to the larger number of variables
+    pub fn is_equivalent_with(
+        &self,
+        cubelist_x: &CubeList,
+        options: &UrpOptions,
+    ) -> Result<bool, UrpError> {
+        let num_var = self.num_var().max(cubelist_x.num_var());
+        let (cubelist_a, cubelist_b) = (self.extend_vars(num_var), cubelist_x.extend_vars(num_var));
+        for (cubelist, cubelist_y) in [(&cubelist_a, &cubelist_b), (&cubelist_b, &cubelist_a)] {
+            for cube in &cubelist.0 {
+                if !cubelist_y.covers_cube_with(cube, options)? {
+                    return Ok(false);
+                }
+            }
+        }
+        Ok(true)
+    }
+
+    /// This function returns an irredundant cover of the function,
+    /// from which no Cube can be removed without changing the function
+    ///
+    /// The Cubes are classified as
+    /// * relatively essential - not covered by the rest of the Cubes
+    /// * totally redundant - covered by the relatively essential Cubes
+    /// * partially redundant - all the other Cubes
+    ///
+    /// Partially redundant Cubes are then dropped one at a time, starting
+    /// from the ones with the most literals, as long as the remaining
+    /// Cubes still cover them
+    pub fn make_irredundant(&self) -> CubeList {
+        let mut essential = CubeList::new(self.1);
+        let mut redundant = Vec::new();
+        for i in 0..self.len() {
+            let mut rest = CubeList::new(self.1);
+            for (j, cube) in self.0.iter().enumerate() {
+                if i != j {
+                    rest.add_cube(cube.clone());
+                }
+            }
+            if rest.covers_cube(&self.0[i]) {
+                redundant.push(self.0[i].clone());
+            } else {
+                essential.add_cube(self.0[i].clone());
+            }
+        }
+
+        let mut partial: Vec<Cube> = redundant
+            .into_iter()
+            .filter(|cube| !essential.covers_cube(cube))
+            .collect();
+        partial.sort_by_key(|cube| std::cmp::Reverse(cube.num_literals()));
+        let mut i = 0;
+        while i < partial.len() {
+            let mut rest = essential.clone();
+            for (j, cube) in partial.iter().enumerate() {
+                if i != j {
+                    rest.add_cube(cube.clone());
+                }
+            }
+            if rest.covers_cube(&partial[i]) {
+                partial.remove(i);
+            } else {
+                i += 1;
+            }
+        }
+
+        for cube in partial {
+            essential.add_cube(cube);
+        }
+        essential
+    }
+
+    /// This function returns a copy of the CubeList without the
+    /// Cubes that are contained in another Cube of the CubeList
+    pub fn single_cube_containment(&self) -> CubeList {
+        let mut cubelist = CubeList::new(self.1);
+        for (i, cube) in self.0.iter().enumerate() {
+            let contained = self.0.iter().enumerate().any(|(j, cube_x)| {
+                i != j && cube_x.contains(cube) && (!cube.contains(cube_x) || j < i)
+            });
+            if !contained {
+                cubelist.add_cube(cube.clone());
+            }
+        }
+        cubelist
+    }
+
+    /// This function returns all the prime implicants of the function
+    ///
+    /// The primes of a unate function are the Cubes left after single cube
+    /// containment. Otherwise the function is split on a binate variable x
+    /// and the primes are merged from the primes P1 of F_x and P0 of F_x'
+    /// as x.P1 + x'.P0 + (P1 AND P0), again followed by single cube containment
+    pub fn primes(&self) -> CubeList {
+        if self.is_unate() {
+            self.single_cube_containment()
+        } else {
+            let var_num = self.get_most_unate_var();
+            let (pos_cubelist, neg_cubelist) = self.cofactor(var_num);
+            let pos_primes = pos_cubelist.primes();
+            let neg_primes = neg_cubelist.primes();
+            let pos_cube = Cube::get_var_cube(self.1, var_num, true);
+            let neg_cube = Cube::get_var_cube(self.1, var_num, false);
+            let mut cubelist = pos_primes.and(&neg_primes);
+            for cube in &pos_primes.0 {
+                cubelist.add_cube(cube.and(&pos_cube).unwrap());
+            }
+            for cube in &neg_primes.0 {
+                cubelist.add_cube(cube.and(&neg_cube).unwrap());
+            }
+            cubelist.single_cube_containment()
+        }
+    }
+
+    /// This function returns a cover of the function with the minimum
+    /// number of Cubes, along with a boolean value that is true if the
+    /// result is proven to be a minimum
+    ///
+    /// The prime table has a row for every minterm of the function and
+    /// a column for every prime implicant. The unate covering problem on
+    /// this table is solved by branch and bound. If the function has too
+    /// many minterms or the search takes too long, the best cover found
+    /// is returned and it is not proven to be a minimum
+    pub fn minimize_exact(&self) -> (CubeList, bool) {
+        let primes = self.primes();
+        let mut num_minterms: usize = 0;
+        for cube in &primes.0 {
+            let num_dontcare = (self.1 - cube.num_literals()) as u32;
+            num_minterms = num_minterms.saturating_add(2_usize.saturating_pow(num_dontcare));
+        }
+        if num_minterms > MAX_EXACT_MINTERMS {
+            return (primes.make_irredundant(), false);
+        }
+
+        // The minterms are sorted, so the rows and the cover do not vary between runs
+        let mut minterms = BTreeSet::new();
+        for cube in &primes.0 {
+            minterms.extend(cube.minterms());
+        }
+        let rows: Vec<Vec<usize>> = minterms
+            .iter()
+            .map(|minterm| {
+                (0..primes.len())
+                    .filter(|i| primes.0[*i].contains(minterm))
+                    .collect()
+            })
+            .collect();
+        let (columns, optimal) = cover::minimum_cover(&rows, MAX_EXACT_NODES);
+
+        let mut cubelist = CubeList::new(self.1);
+        for column in columns {
+            cubelist.add_cube(primes.0[column].clone());
+        }
+        (cubelist, optimal)
+    }
+
     /// This function returns a boolean value that indicates if the function
     /// represented by the CubeList is a tautology
     pub fn is_tautology(&self) -> bool {
-        if self.is_unate() {
-            if self.contains_cube(&Cube::from(vec![0; self.1])) {
-                true
-            } else {
-                false
+        // Without limits or a cancellation token the check cannot fail
+        self.is_tautology_with(&UrpOptions::default()).unwrap()
+    }
+
+    /// This function returns if the function is a tautology like is_tautology,
+    /// with the cofactors of the first levels of the recursion checked in
+    /// parallel when the parallel feature is enabled
+    ///
+    /// As soon as a cofactor is found not to be a tautology, the checks
+    /// of the other cofactors stop
+    pub fn is_tautology_parallel(&self) -> bool {
+        self.is_tautology_with(&UrpOptions {
+            parallel: true,
+            ..UrpOptions::default()
+        })
+        .unwrap()
+    }
+
+    /// This function returns if the function is a tautology like is_tautology,
+    /// reusing the results for sub-covers stored in the cache and storing
+    /// the new ones
+    pub fn is_tautology_cached(&self, cache: &ComputedTable) -> bool {
+        self.is_tautology_with(&UrpOptions {
+            cache: Some(cache),
+            ..UrpOptions::default()
+        })
+        .unwrap()
+    }
+
+    /// This function returns if the function is a tautology, checked
+    /// with the given options
+    ///
+    /// Before splitting a cover, the rules of Rule are tried in order: a
+    /// Cube without literals, Cubes x and x' of a single variable, too few
+    /// minterms, unate covers, unate reduction and variable disjoint components.
+    /// Returns a LimitExceeded error if one of the limits of the options
+    /// is exceeded or the operation is cancelled
+    pub fn is_tautology_with(&self, options: &UrpOptions) -> Result<bool, UrpError> {
+        let run = Run {
+            options,
+            deadline: options.deadline(),
+        };
+        // The check only stops early after a cofactor was found not to be a tautology
+        let result = self.is_tautology_split(0, None, &run, &AtomicBool::new(false))?;
+        Ok(result.unwrap_or(false))
+    }
+
+    /// Returns if the cover at the given depth of the recursion, derived from
+    /// the cover of the traced node parent, is a tautology
+    ///
+    /// Returns None if the check stopped early because failed was set by
+    /// another cofactor. failed is set when the result is false or an error
+    fn is_tautology_split(
+        &self,
+        depth: usize,
+        parent: Option<usize>,
+        run: &Run,
+        failed: &AtomicBool,
+    ) -> Result<Option<bool>, UrpError> {
+        if failed.load(Ordering::Relaxed) {
+            return Ok(None);
+        }
+        let node = run.enter(parent, Operation::Tautology, depth, self.len());
+        let result = self.tautology_step(depth, node, run, failed);
+        run.leave(node);
+        if matches!(result, Ok(Some(false)) | Err(_)) {
+            failed.store(true, Ordering::Relaxed);
+        }
+        result
+    }
+
+    /// Applies the first rule of is_tautology_with that holds for the cover
+    /// of the traced node
+    fn tautology_step(
+        &self,
+        depth: usize,
+        node: Option<usize>,
+        run: &Run,
+        failed: &AtomicBool,
+    ) -> Result<Option<bool>, UrpError> {
+        let options = run.options;
+        if self.contains_cube(&Cube::from(vec![0; self.1])) {
+            run.apply(node, Rule::DontCareRow);
+            return Ok(Some(true));
+        }
+        if self.has_complementary_singletons() {
+            run.apply(node, Rule::SingleVariable);
+            return Ok(Some(true));
+        }
+        if self.too_few_minterms() {
+            run.apply(node, Rule::MintermCount);
+            return Ok(Some(false));
+        }
+        let binate = self.binate_vars();
+        if binate.is_empty() {
+            run.apply(node, Rule::Unate);
+            return Ok(Some(false));
+        }
+        options.check(depth, run.deadline)?;
+        if binate.len() < self.support().len() {
+            // The cover is a tautology if and only if the Cubes without
+            // literals of unate variables are a tautology
+            run.apply(node, Rule::UnateReduction);
+            let cubes = self
+                .0
+                .iter()
+                .filter(|cube| {
+                    (1..=self.1).all(|i| {
+                        cube.get_literal(i).unwrap() == Literal::Dontcare || binate.contains(&i)
+                    })
+                })
+                .cloned()
+                .collect();
+            let reduced = CubeList(cubes, self.1);
+            return reduced.is_tautology_split(depth, node, run, failed);
+        }
+
+        let cache = options.cache;
+        let key = cache.map(|_| Key::new(self));
+        if let (Some(cache), Some(key)) = (cache, &key) {
+            if let Some(result) = cache.get_tautology(key) {
+                run.step(node, Step::Cached);
+                return Ok(Some(result));
             }
+        }
+        let components = self.components();
+        let result = if components.len() > 1 {
+            // The cover is a tautology if and only if one of its components is.
+            // Each component is checked on its own, as one of them not being a
+            // tautology does not decide the result
+            run.apply(node, Rule::Components);
+            let mut result = Some(false);
+            for component in &components {
+                let failed = AtomicBool::new(false);
+                if component.is_tautology_split(depth, node, run, &failed)? == Some(true) {
+                    result = Some(true);
+                    break;
+                }
+            }
+            result
         } else {
-            let var_num = self.get_most_unate_var();
+            run.apply(node, Rule::Split);
+            let var_num = self.split_var(options.split);
+            run.split(node, var_num);
             let (pos_cubelist, neg_cubelist) = self.cofactor(var_num);
-            pos_cubelist.is_tautology() && neg_cubelist.is_tautology()
+            if run.parallel(depth, self.len()) {
+                let (pos_result, neg_result) = join(
+                    || pos_cubelist.is_tautology_split(depth + 1, node, run, failed),
+                    || neg_cubelist.is_tautology_split(depth + 1, node, run, failed),
+                );
+                match (pos_result?, neg_result?) {
+                    (Some(false), _) | (_, Some(false)) => Some(false),
+                    (Some(true), Some(true)) => Some(true),
+                    _ => None,
+                }
+            } else {
+                match pos_cubelist.is_tautology_split(depth + 1, node, run, failed)? {
+                    Some(true) => neg_cubelist.is_tautology_split(depth + 1, node, run, failed)?,
+                    result => result,
+                }
+            }
+        };
+        if let (Some(cache), Some(key), Some(result)) = (cache, key, result) {
+            cache.insert_tautology(key, result);
         }
+        Ok(result)
+    }
+
+    /// Returns true if the cover has the single literal Cubes x and x'
+    /// of some variable x
+    fn has_complementary_singletons(&self) -> bool {
+        let mut positive = vec![false; self.1];
+        let mut negative = vec![false; self.1];
+        for cube in self.0.iter().filter(|cube| cube.num_literals() == 1) {
+            for i in 1..=self.1 {
+                match cube.get_literal(i).unwrap() {
+                    Literal::Positive => positive[i - 1] = true,
+                    Literal::Negative => negative[i - 1] = true,
+                    Literal::Dontcare => {}
+                }
+            }
+        }
+        positive
+            .iter()
+            .zip(&negative)
+            .any(|(pos, neg)| *pos && *neg)
+    }
+
+    /// Returns true if the Cubes together have less than 2^N minterms,
+    /// counting the shared minterms again for each Cube, so that they
+    /// cannot cover all the 2^N minterms
+    fn too_few_minterms(&self) -> bool {
+        // Larger functions are not checked, as 2^N does not fit in a u128
+        if self.1 >= 127 {
+            return false;
+        }
+        let count = self.0.iter().fold(0u128, |count, cube| {
+            count.saturating_add(1 << (self.1 - cube.num_literals()))
+        });
+        count < 1 << self.1
+    }
+
+    /// This function returns the variable disjoint components of the cover:
+    /// Cubes that share a variable are in the same component, and no variable
+    /// appears in the Cubes of two components
+    ///
+    /// The components are in the order of their first Cube, and the function
+    /// is the OR of its components. Each component has the number of
+    /// variables of the function. An empty cover has no component
+    pub fn components(&self) -> Vec<CubeList> {
+        // The component of each variable, merged with union find
+        let mut parent: Vec<usize> = (0..=self.1).collect();
+        fn find(parent: &mut [usize], var: usize) -> usize {
+            let mut root = var;
+            while parent[root] != root {
+                root = parent[root];
+            }
+            parent[var] = root;
+            root
+        }
+        for cube in &self.0 {
+            let mut vars =
+                (1..=self.1).filter(|i| cube.get_literal(*i).unwrap() != Literal::Dontcare);
+            if let Some(first) = vars.next() {
+                for var in vars {
+                    let (root_1, root_2) = (find(&mut parent, first), find(&mut parent, var));
+                    parent[root_2] = root_1;
+                }
+            }
+        }
+        let mut roots: Vec<usize> = vec![];
+        let mut components: Vec<CubeList> = vec![];
+        for cube in &self.0 {
+            let var = (1..=self.1)
+                .find(|i| cube.get_literal(*i).unwrap() != Literal::Dontcare)
+                .unwrap_or(0);
+            let root = find(&mut parent, var);
+            let index = match roots.iter().position(|r| *r == root) {
+                Some(index) => index,
+                None => {
+                    roots.push(root);
+                    components.push(CubeList::new(self.1));
+                    roots.len() - 1
+                }
+            };
+            components[index].add_cube(cube.clone());
+        }
+        components
+    }
+
+    /// This function returns the Cubes of the function in sorted order,
+    /// without duplicates, so that functions with the same Cubes in any
+    /// order have the same canonical Cubes
+    pub fn canonical_cubes(&self) -> Vec<Cube> {
+        let mut cubes = self.0.clone();
+        cubes.sort_unstable();
+        cubes.dedup();
+        cubes
+    }
+
+    /// This function returns a hash of the number of variables and the
+    /// canonical Cubes of the function, which does not depend on the order
+    /// of the Cubes
+    pub fn canonical_hash(&self) -> u64 {
+        Key::new(self).hash()
     }
 
     /// This function returns if the function is unate
     pub fn is_unate(&self) -> bool {
+        self.binate_vars().is_empty()
+    }
+
+    /// Returns the number of positive and negative literals of each variable
+    fn literal_counts(&self) -> (Vec<usize>, Vec<usize>) {
         let mut pos_count = vec![0; self.1];
         let mut neg_count = vec![0; self.1];
         for cube in &self.0 {
@@ -253,44 +1068,256 @@ impl CubeList {
                 }
             }
         }
-        for i in 1..=self.1 {
-            if pos_count[i - 1] != 0 && neg_count[i - 1] != 0 {
-                return false;
+        (pos_count, neg_count)
+    }
+
+    /// This function returns the variables present in at least one Cube
+    pub fn support(&self) -> Vec<usize> {
+        let (pos_count, neg_count) = self.literal_counts();
+        (1..=self.1)
+            .filter(|i| pos_count[i - 1] != 0 || neg_count[i - 1] != 0)
+            .collect()
+    }
+
+    /// This function returns the variables present as both
+    /// a positive and a negative literal
+    pub fn binate_vars(&self) -> Vec<usize> {
+        let (pos_count, neg_count) = self.literal_counts();
+        (1..=self.1)
+            .filter(|i| pos_count[i - 1] != 0 && neg_count[i - 1] != 0)
+            .collect()
+    }
+
+    /// This function returns the total number of literals of all the Cubes
+    pub fn num_literals(&self) -> usize {
+        self.0.iter().map(|cube| cube.num_literals()).sum()
+    }
+
+    /// This function returns the number of minterms of the function,
+    /// counting each minterm once even if it is in many Cubes
+    ///
+    /// The count saturates at u128::MAX for functions of more than 127 variables
+    pub fn minterm_count(&self) -> u128 {
+        let all_minterms = 1_u128.checked_shl(self.1 as u32).unwrap_or(u128::MAX);
+        if self.is_empty() {
+            0
+        } else if self.contains_cube(&Cube::new(self.1)) {
+            all_minterms
+        } else if self.len() == 1 {
+            all_minterms >> self.0[0].num_literals()
+        } else {
+            // Each cofactor counts its minterms for both values of the variable
+            let var_num = self.get_most_unate_var();
+            let (pos_cubelist, neg_cubelist) = self.cofactor(var_num);
+            (pos_cubelist.minterm_count() / 2).saturating_add(neg_cubelist.minterm_count() / 2)
+        }
+    }
+
+    /// This function returns the function as a sum of products, e.g. "a b c' + d"
+    ///
+    /// The variables are named as in Cube::to_sop_string and a
+    /// function without any Cube is written as "0"
+    pub fn to_sop_string(&self) -> String {
+        if self.is_empty() {
+            "0".to_string()
+        } else {
+            self.0
+                .iter()
+                .map(|cube| cube.to_sop_string())
+                .collect::<Vec<String>>()
+                .join(" + ")
+        }
+    }
+}
+
+/// Returns the variable chosen by the MostBinate strategy from the numbers
+/// of positive and negative literals of each variable
+///
+/// While no binate variable was seen, the unate variable with the most
+/// literals is kept. From the first binate variable on, the variable with
+/// the most literals is kept, binate or not. If two of them had the same
+/// number of literals, the binate variable with the smallest difference
+/// between its positive and negative literals is chosen instead
+fn most_binate_var(pos_count: &[usize], neg_count: &[usize]) -> usize {
+    let mut unate = true;
+    let mut max_var = 0;
+    let mut max_val = 0;
+    let mut more_than_one = false;
+    for (i, (pos, neg)) in pos_count.iter().zip(neg_count).enumerate() {
+        let count = pos + neg;
+        if (*pos == 0 || *neg == 0) && unate {
+            if max_val < count {
+                max_val = count;
+                max_var = i + 1;
+                more_than_one = false;
+            } else if max_val == count {
+                more_than_one = true;
             }
+        } else if unate {
+            // The first binate variable
+            unate = false;
+            max_val = count;
+            max_var = i + 1;
+            more_than_one = false;
+        } else if max_val < count {
+            max_val = count;
+            max_var = i + 1;
+        } else if max_val == count {
+            more_than_one = true;
+        }
+    }
+    if !more_than_one || unate {
+        return max_var;
+    }
+    let mut min_bal = usize::MAX;
+    let mut min_var = 0;
+    for (i, (pos, neg)) in pos_count.iter().zip(neg_count).enumerate() {
+        if *pos != 0 && *neg != 0 && min_bal > pos.abs_diff(*neg) {
+            min_bal = pos.abs_diff(*neg);
+            min_var = i + 1;
         }
-        true
     }
+    min_var
 }
 
 /// This implements conversion from a vector of vectors of i32 to a CubeList
 ///
-/// Each vector present in the vector represents a cube
+/// Each vector present in the vector represents a cube.
+/// An empty vector is converted to an empty CubeList with no variables
 ///
 impl From<Vec<Vec<i32>>> for CubeList {
     fn from(vector: Vec<Vec<i32>>) -> Self {
-        let mut cubelist = CubeList::new(vector[0].len());
-        for i in 0..vector.len() {
-            cubelist.add_cube(Cube::from(vector[i].clone()));
+        let mut cubelist = CubeList::new(vector.first().map_or(0, |cube| cube.len()));
+        for cube_vector in vector {
+            cubelist.add_cube(Cube::from(cube_vector));
         }
         cubelist
     }
 }
 
+/// This implements collecting Cubes into a CubeList
+///
+/// The number of variables is the length of the longest Cube,
+/// and shorter Cubes are extended to that length
+impl std::iter::FromIterator<Cube> for CubeList {
+    fn from_iter<I: IntoIterator<Item = Cube>>(iter: I) -> Self {
+        let cubes: Vec<Cube> = iter.into_iter().collect();
+        let num_var = cubes.iter().map(|cube| cube.len()).max().unwrap_or(0);
+        CubeList(
+            cubes
+                .into_iter()
+                .map(|cube| cube.extend_vars(num_var))
+                .collect(),
+            num_var,
+        )
+    }
+}
+
+impl IntoIterator for CubeList {
+    type Item = Cube;
+    type IntoIter = std::vec::IntoIter<Cube>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.into_iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a CubeList {
+    type Item = &'a Cube;
+    type IntoIter = std::slice::Iter<'a, Cube>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.iter()
+    }
+}
+
+/// Two CubeLists are equal if they represent the same boolean function,
+/// even if their Cubes are different. If the number of variables differ,
+/// both functions are first extended to the larger number of variables
+impl PartialEq for CubeList {
+    fn eq(&self, other: &CubeList) -> bool {
+        // Without limits or a cancellation token the check cannot fail
+        self.is_equivalent_with(other, &UrpOptions::default())
+            .unwrap()
+    }
+}
+
+/// Implements a binary operator for all the combinations of
+/// owned and borrowed CubeLists using the given CubeList method
+macro_rules! impl_binary_op {
+    ($trait:ident, $op:ident, $method:ident) => {
+        impl std::ops::$trait<&CubeList> for &CubeList {
+            type Output = CubeList;
+
+            fn $op(self, cubelist_x: &CubeList) -> CubeList {
+                self.$method(cubelist_x)
+            }
+        }
+
+        impl std::ops::$trait<CubeList> for &CubeList {
+            type Output = CubeList;
+
+            fn $op(self, cubelist_x: CubeList) -> CubeList {
+                self.$method(&cubelist_x)
+            }
+        }
+
+        impl std::ops::$trait<&CubeList> for CubeList {
+            type Output = CubeList;
+
+            fn $op(self, cubelist_x: &CubeList) -> CubeList {
+                self.$method(cubelist_x)
+            }
+        }
+
+        impl std::ops::$trait<CubeList> for CubeList {
+            type Output = CubeList;
+
+            fn $op(self, cubelist_x: CubeList) -> CubeList {
+                self.$method(&cubelist_x)
+            }
+        }
+    };
+}
+
+impl_binary_op!(BitAnd, bitand, and);
+impl_binary_op!(BitOr, bitor, or);
+impl_binary_op!(BitXor, bitxor, xor);
+
+impl std::ops::Not for &CubeList {
+    type Output = CubeList;
+
+    fn not(self) -> CubeList {
+        self.complement()
+    }
+}
+
+impl std::ops::Not for CubeList {
+    type Output = CubeList;
+
+    fn not(self) -> CubeList {
+        self.complement()
+    }
+}
+
 impl std::fmt::Display for CubeList {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> Result<(), std::fmt::Error> {
-        if self.0.len() > 0 {
-            writeln!(f, "{}", self.0[0].len());
-            writeln!(f, "{}", self.0.len());
-            for cube in &self.0 {
-                writeln!(f, "{}", cube);
-            }
+        writeln!(f, "{}", self.1)?;
+        writeln!(f, "{}", self.0.len())?;
+        for cube in &self.0 {
+            writeln!(f, "{}", cube)?;
         }
         Ok(())
     }
 }
 
+#[cfg(test)]
+#[allow(clippy::bool_assert_comparison)]
 mod test {
     use super::*;
+    use crate::oracle::{self, minterm_cube, truth_table, MAX_VARS};
+    use crate::rules::RuleCounters;
+    use proptest::prelude::*;
 
     #[test]
     fn cubelist_from_vec_vec_i32_and_contains_cube() {
@@ -301,6 +1328,142 @@ mod test {
         assert_eq!(cubelist.contains_cube(&Cube::from(vec![0, 0, 1])), true);
     }
 
+    #[test]
+    fn from_empty_vector() {
+        let cubelist = CubeList::from(vec![]);
+        assert_eq!(cubelist.len(), 0);
+        assert_eq!(cubelist.num_var(), 0);
+    }
+
+    #[test]
+    fn read_from_file() {
+        let file = std::env::temp_dir().join("urp_read_from_file.pcn");
+        let file = file.to_str().unwrap();
+        std::fs::write(file, "3\n2\n2 1 -3\n1 2\n").unwrap();
+        let cubelist = CubeList::read_from_file(file).unwrap();
+        assert_eq!(cubelist.len(), 2);
+        assert_eq!(cubelist.contains_cube(&Cube::from(vec![1, 0, -1])), true);
+        assert_eq!(cubelist.contains_cube(&Cube::from(vec![0, 1, 0])), true);
+
+        std::fs::write(file, "3\n1\n2 1 x\n").unwrap();
+        match CubeList::read_from_file(file) {
+            Err(UrpError::Parse { line, column, .. }) => assert_eq!((line, column), (3, 5)),
+            result => panic!("expected a parse error, found {:?}", result),
+        }
+        std::fs::write(file, "3\n1\n1 4\n").unwrap();
+        assert_eq!(CubeList::read_from_file(file).is_err(), true);
+
+        // An empty function can be written and read back
+        CubeList::new(3).write_to_file(file).unwrap();
+        let cubelist = CubeList::read_from_file(file).unwrap();
+        assert_eq!((cubelist.len(), cubelist.num_var()), (0, 3));
+        std::fs::remove_file(file).unwrap();
+        assert_eq!(CubeList::read_from_file(file).is_err(), true);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn json() {
+        let cubelist = CubeList::from(vec![vec![1, 0, -1], vec![0, 1, 0]]);
+        let json = serde_json::to_string(&cubelist).unwrap();
+        assert_eq!(json, r#"{"num_var":3,"cubes":["1-0","-1-"]}"#);
+        let result: CubeList = serde_json::from_str(&json).unwrap();
+        assert_eq!(result.num_var(), 3);
+        assert_eq!(result.contains_cube(&Cube::from(vec![1, 0, -1])), true);
+        assert_eq!(
+            serde_json::from_str::<CubeList>(r#"{"num_var":2,"cubes":["1-0"]}"#).is_err(),
+            true
+        );
+
+        let file = std::env::temp_dir().join("urp_json.json");
+        let file = file.to_str().unwrap();
+        cubelist.write_json(file).unwrap();
+        assert_eq!(CubeList::read_json(file).unwrap(), cubelist);
+        std::fs::remove_file(file).unwrap();
+    }
+
+    #[test]
+    fn and_or_with_different_num_var() {
+        let cubelist_x = CubeList::from(vec![vec![1, 0]]);
+        let cubelist_y = CubeList::from(vec![vec![0, 0, -1]]);
+        let result = cubelist_x.and(&cubelist_y);
+        assert_eq!(result.num_var(), 3);
+        assert_eq!(result.contains_cube(&Cube::from(vec![1, 0, -1])), true);
+        let result = cubelist_x.or(&cubelist_y);
+        assert_eq!(result.num_var(), 3);
+        assert_eq!(result.len(), 2);
+        assert_eq!(result.contains_cube(&Cube::from(vec![1, 0, 0])), true);
+        assert_eq!(result.contains_cube(&Cube::from(vec![0, 0, -1])), true);
+    }
+
+    #[test]
+    fn permute() {
+        let cubelist = CubeList::from(vec![vec![1, -1, 0], vec![0, 1, 1]]);
+        let result = cubelist.permute(&[2, 3, 1]).unwrap();
+        assert_eq!(result.num_var(), 3);
+        assert_eq!(result.contains_cube(&Cube::from(vec![0, 1, -1])), true);
+        assert_eq!(result.contains_cube(&Cube::from(vec![1, 0, 1])), true);
+
+        // Renaming into a larger variable space
+        let result = cubelist.permute(&[1, 2, 5]).unwrap();
+        assert_eq!(result.num_var(), 5);
+        assert_eq!(result.contains_cube(&Cube::from(vec![0, 1, 0, 0, 1])), true);
+
+        assert_eq!(cubelist.permute(&[1, 2]).is_err(), true);
+        assert_eq!(cubelist.permute(&[1, 1, 2]).is_err(), true);
+        assert_eq!(cubelist.permute(&[0, 1, 2]).is_err(), true);
+    }
+
+    #[test]
+    fn operators() {
+        let cubelist_a = CubeList::from(vec![vec![1, 0, 0]]);
+        let cubelist_b = CubeList::from(vec![vec![0, 1, 0]]);
+        let cubelist_c = CubeList::from(vec![vec![0, 0, 1]]);
+        assert_eq!(&cubelist_a & &cubelist_b, cubelist_a.and(&cubelist_b));
+        assert_eq!(
+            (&cubelist_a & &cubelist_b) | !&cubelist_c,
+            cubelist_a.and(&cubelist_b).or(&cubelist_c.complement())
+        );
+        // a XOR b = a b' + a' b
+        assert_eq!(
+            &cubelist_a ^ &cubelist_b,
+            CubeList::from(vec![vec![1, -1, 0], vec![-1, 1, 0]])
+        );
+        assert_eq!((&cubelist_a ^ &cubelist_a).is_empty(), true);
+        assert_eq!((cubelist_a.clone() | !cubelist_a).is_tautology(), true);
+    }
+
+    #[test]
+    fn semantic_equality() {
+        // x1 + x1' x2 is the same function as x1 + x2
+        let cubelist_a = CubeList::from(vec![vec![1, 0], vec![-1, 1]]);
+        let cubelist_b = CubeList::from(vec![vec![1, 0], vec![0, 1]]);
+        assert_eq!(cubelist_a, cubelist_b);
+        assert_ne!(cubelist_a, CubeList::from(vec![vec![1, 0]]));
+        assert_eq!(
+            cubelist_b,
+            CubeList::from(vec![vec![1, 0, 0], vec![0, 1, 0]])
+        );
+    }
+
+    #[test]
+    fn iterators() {
+        let cubelist = CubeList::from(vec![vec![1, 0], vec![-1, 1]]);
+        assert_eq!(cubelist.iter().count(), 2);
+        assert_eq!(
+            (&cubelist).into_iter().next(),
+            Some(&Cube::from(vec![1, 0]))
+        );
+        let collected: CubeList = cubelist
+            .clone()
+            .into_iter()
+            .chain(vec![Cube::from(vec![0, 0, 1])])
+            .collect();
+        assert_eq!(collected.num_var(), 3);
+        assert_eq!(collected.len(), 3);
+        assert_eq!(collected.contains_cube(&Cube::from(vec![1, 0, 0])), true);
+    }
+
     #[test]
     fn cofactor() {
         let cubelist = CubeList::from(vec![vec![1, -1, -1], vec![-1, 1, -1], vec![0, 1, 1]]);
@@ -319,6 +1482,171 @@ mod test {
         assert_eq!(neg_cubelist.contains_cube(&Cube::from(vec![0, 1, 1])), true);
     }
 
+    #[test]
+    fn compose() {
+        // F = x1 x2 + x1' x3, substituting x1 := x2 gives x2 + x2' x3
+        let cubelist_f = CubeList::from(vec![vec![1, 1, 0], vec![-1, 0, 1]]);
+        let cubelist_g = CubeList::from(vec![vec![0, 1, 0]]);
+        let result = cubelist_f.compose(1, &cubelist_g).unwrap();
+        assert_eq!(result.contains_cube(&Cube::from(vec![0, 1, 0])), true);
+        assert_eq!(result.contains_cube(&Cube::from(vec![0, -1, 1])), true);
+
+        // Substituting a constant 1 gives the positive cofactor
+        let cubelist_one = CubeList::from(vec![vec![0, 0, 0]]);
+        let result = cubelist_f.compose(1, &cubelist_one).unwrap();
+        assert_eq!(cubelist_f.compose(0, &cubelist_one).is_err(), true);
+        assert_eq!(cubelist_f.compose(4, &cubelist_one).is_err(), true);
+        assert_eq!(result.len(), 1);
+        assert_eq!(result.contains_cube(&Cube::from(vec![0, 1, 0])), true);
+    }
+
+    #[test]
+    fn covers_cube() {
+        let cubelist = CubeList::from(vec![vec![1, 1, 0], vec![-1, 0, 1]]);
+        assert_eq!(cubelist.covers_cube(&Cube::from(vec![0, 1, 1])), true);
+        assert_eq!(cubelist.covers_cube(&Cube::from(vec![1, 1, -1])), true);
+        assert_eq!(cubelist.covers_cube(&Cube::from(vec![0, 0, 1])), false);
+
+        // The consensus term x2 x3 is only covered by both Cubes together
+        let consensus = cubelist.or(&CubeList::from(vec![vec![0, 1, 1]]));
+        let options = UrpOptions::default();
+        assert_eq!(
+            consensus.is_equivalent_with(&cubelist, &options).unwrap(),
+            true
+        );
+        let other = CubeList::from(vec![vec![1, 1, 0], vec![-1, 0, 0]]);
+        assert_eq!(
+            other.is_equivalent_with(&cubelist, &options).unwrap(),
+            false
+        );
+        // The 8 minterms of 3 variables are only known to cover the Cube
+        // without literals after a split
+        let minterms = CubeList::from(
+            (0..8)
+                .map(|i| {
+                    (0..3)
+                        .map(|j| if i >> j & 1 == 1 { 1 } else { -1 })
+                        .collect()
+                })
+                .collect::<Vec<Vec<i32>>>(),
+        );
+        let one = CubeList::from(vec![vec![0, 0, 0]]);
+        assert_eq!(minterms.is_equivalent_with(&one, &options).unwrap(), true);
+        let limited = UrpOptions {
+            limits: crate::options::Limits {
+                max_depth: Some(0),
+                ..Default::default()
+            },
+            ..UrpOptions::default()
+        };
+        assert_eq!(minterms.is_equivalent_with(&one, &limited).is_err(), true);
+    }
+
+    #[test]
+    fn make_irredundant() {
+        // The consensus term x2 x3 is totally redundant
+        let cubelist = CubeList::from(vec![vec![1, 1, 0], vec![-1, 0, 1], vec![0, 1, 1]]);
+        let result = cubelist.make_irredundant();
+        assert_eq!(result.len(), 2);
+        assert_eq!(result.contains_cube(&Cube::from(vec![0, 1, 1])), false);
+
+        // Only one of the partially redundant duplicates is kept
+        let cubelist = CubeList::from(vec![vec![1, 0], vec![1, 0], vec![-1, 0]]);
+        assert_eq!(cubelist.make_irredundant().len(), 2);
+
+        // x1 x2' + x2 x3' + x1' x3 + x2' x3 + x1 x3' has two partially
+        // redundant cubes and the result keeps only one of them
+        let cubelist = CubeList::from(vec![
+            vec![1, -1, 0],
+            vec![0, 1, -1],
+            vec![-1, 0, 1],
+            vec![0, -1, 1],
+            vec![1, 0, -1],
+        ]);
+        let result = cubelist.make_irredundant();
+        assert_eq!(result.len(), 4);
+        for cube in &cubelist.0 {
+            assert_eq!(result.covers_cube(cube), true);
+        }
+    }
+
+    #[test]
+    fn single_cube_containment() {
+        let cubelist = CubeList::from(vec![vec![1, 0, 0], vec![1, 1, 0], vec![1, 0, 0]]);
+        let result = cubelist.single_cube_containment();
+        assert_eq!(result.len(), 1);
+        assert_eq!(result.contains_cube(&Cube::from(vec![1, 0, 0])), true);
+    }
+
+    #[test]
+    fn primes() {
+        // x1 x2 + x1' x3 has the consensus x2 x3 as its third prime
+        let cubelist = CubeList::from(vec![vec![1, 1, 0], vec![-1, 0, 1]]);
+        let result = cubelist.primes();
+        assert_eq!(result.len(), 3);
+        assert_eq!(result.contains_cube(&Cube::from(vec![1, 1, 0])), true);
+        assert_eq!(result.contains_cube(&Cube::from(vec![-1, 0, 1])), true);
+        assert_eq!(result.contains_cube(&Cube::from(vec![0, 1, 1])), true);
+
+        // Minterms x1 x2 + x1 x2' merge into the prime x1
+        let cubelist = CubeList::from(vec![vec![1, 1], vec![1, -1]]);
+        let result = cubelist.primes();
+        assert_eq!(result.len(), 1);
+        assert_eq!(result.contains_cube(&Cube::from(vec![1, 0])), true);
+
+        // The primes of a tautology is the universal cube
+        let cubelist = CubeList::from(vec![vec![1, 0], vec![-1, 1], vec![0, -1]]);
+        let result = cubelist.primes();
+        assert_eq!(result.len(), 1);
+        assert_eq!(result.contains_cube(&Cube::from(vec![0, 0])), true);
+    }
+
+    #[test]
+    fn minimize_exact() {
+        // x1 x2' + x2 x3' + x1' x3 + x2' x3 + x1 x3' + x1' x2 has 6 primes
+        // in a cyclic table and a minimum cover of 3 Cubes
+        let cubelist = CubeList::from(vec![
+            vec![1, -1, 0],
+            vec![0, 1, -1],
+            vec![-1, 0, 1],
+            vec![0, -1, 1],
+            vec![1, 0, -1],
+            vec![-1, 1, 0],
+        ]);
+        let (result, optimal) = cubelist.minimize_exact();
+        assert_eq!(optimal, true);
+        assert_eq!(result.len(), 3);
+        for cube in &cubelist.0 {
+            assert_eq!(result.covers_cube(cube), true);
+        }
+        for cube in &result.0 {
+            assert_eq!(cubelist.covers_cube(cube), true);
+        }
+        // The table has two minimum covers, and every run picks the same one
+        for _ in 0..10 {
+            assert_eq!(cubelist.minimize_exact().0 .0, result.0);
+        }
+
+        // The minimum cover of a function without minterms is empty
+        let (result, optimal) = CubeList::new(3).minimize_exact();
+        assert_eq!(optimal, true);
+        assert_eq!(result.len(), 0);
+    }
+
+    #[test]
+    fn stats() {
+        let cubelist = CubeList::from(vec![vec![1, 1, 0, 0], vec![-1, 0, 1, 0], vec![0, 1, 1, 0]]);
+        assert_eq!(cubelist.num_literals(), 6);
+        assert_eq!(cubelist.support(), vec![1, 2, 3]);
+        assert_eq!(cubelist.binate_vars(), vec![1]);
+        // a b + a' c covers 8 of the 16 minterms and b c adds no new minterm
+        assert_eq!(cubelist.minterm_count(), 8);
+        assert_eq!(CubeList::new(3).minterm_count(), 0);
+        assert_eq!(CubeList::from(vec![vec![0, 0, 0]]).minterm_count(), 8);
+        assert_eq!(cubelist.to_sop_string(), "a b + a' c + b c");
+        assert_eq!(CubeList::new(3).to_sop_string(), "0");
+    }
+
     #[test]
     fn is_tautology() {
         let mut cubelist1 = CubeList::from(vec![vec![0, 1, 1], vec![1, 0, 0]]);
@@ -331,4 +1659,458 @@ mod test {
         cubelist2.add_cube(Cube::from(vec![-1, 0, 0]));
         assert_eq!(cubelist2.is_tautology(), true);
     }
+
+    #[test]
+    fn parallel() {
+        // Every function of 3 variables, made of cubes of up to 2 literals
+        let cubes: Vec<Vec<i32>> = (0..27)
+            .map(|i| vec![i % 3 - 1, i / 3 % 3 - 1, i / 9 - 1])
+            .filter(|cube| cube.iter().filter(|literal| **literal != 0).count() < 3)
+            .collect();
+        for mask in (0u32..1 << 19).step_by(97) {
+            let mut cubelist = CubeList::new(3);
+            for (i, cube) in cubes.iter().enumerate() {
+                if mask >> i & 1 == 1 {
+                    cubelist.add_cube(Cube::from(cube.clone()));
+                }
+            }
+            let complement = cubelist.complement();
+            let complement_parallel = cubelist.complement_parallel();
+            assert_eq!(complement.0, complement_parallel.0);
+            assert_eq!(cubelist.is_tautology(), cubelist.is_tautology_parallel());
+        }
+
+        // A larger function, split into tasks
+        let mut cubelist = CubeList::new(12);
+        for i in 1..=12 {
+            cubelist.add_cube(Cube::get_var_cube(12, i, i % 2 == 0));
+            cubelist.add_cube(
+                Cube::get_var_cube(12, i, i % 2 == 1)
+                    .and(&Cube::get_var_cube(12, i % 12 + 1, true))
+                    .unwrap(),
+            );
+        }
+        assert_eq!(cubelist.complement().0, cubelist.complement_parallel().0);
+        assert_eq!(cubelist.is_tautology(), cubelist.is_tautology_parallel());
+        cubelist.add_cube(Cube::get_var_cube(12, 1, false));
+        assert_eq!(cubelist.is_tautology_parallel(), true);
+    }
+
+    #[test]
+    fn cached() {
+        let cache = ComputedTable::new();
+        let cubelist = CubeList::from(vec![
+            vec![1, 1, 0, 0],
+            vec![-1, 0, 1, 0],
+            vec![0, -1, -1, 1],
+            vec![0, 0, 1, -1],
+            vec![-1, -1, 0, 0],
+        ]);
+        let complement = cubelist.complement_cached(&cache);
+        assert_eq!(complement == cubelist.complement(), true);
+        assert_eq!(cache.complement_stats().misses > 0, true);
+        // The second complement is found in the cache
+        let stats = cache.complement_stats();
+        assert_eq!(cubelist.complement_cached(&cache).0, complement.0);
+        assert_eq!(cache.complement_stats().hits, stats.hits + 1);
+
+        assert_eq!(cubelist.is_tautology_cached(&cache), false);
+        assert_eq!(cubelist.is_tautology_cached(&cache), false);
+        assert_eq!(cache.tautology_stats().hits > 0, true);
+        let tautology = cubelist.or(&complement);
+        assert_eq!(tautology.is_tautology_cached(&cache), true);
+        assert_eq!(
+            tautology
+                .is_tautology_with(&UrpOptions {
+                    parallel: true,
+                    cache: Some(&cache),
+                    ..UrpOptions::default()
+                })
+                .unwrap(),
+            tautology.is_tautology()
+        );
+
+        let reordered: CubeList = cubelist.iter().rev().cloned().collect();
+        assert_eq!(reordered.canonical_hash(), cubelist.canonical_hash());
+        assert_eq!(reordered.canonical_cubes(), cubelist.canonical_cubes());
+    }
+
+    #[test]
+    fn tautology_rules() {
+        let rules = RuleCounters::new();
+        let options = UrpOptions {
+            rules: Some(&rules),
+            ..UrpOptions::default()
+        };
+        let check = |cubes: Vec<Vec<i32>>, rule: Rule| {
+            rules.clear();
+            let tautology = CubeList::from(cubes).is_tautology_with(&options).unwrap();
+            assert_eq!(rules.get(rule), 1, "{} not applied", rule);
+            tautology
+        };
+        assert_eq!(check(vec![vec![1, 1], vec![0, 0]], Rule::DontCareRow), true);
+        assert_eq!(
+            check(
+                vec![vec![1, 1, -1], vec![0, -1, 0], vec![0, 1, 0]],
+                Rule::SingleVariable
+            ),
+            true
+        );
+        assert_eq!(
+            check(
+                vec![vec![1, 1], vec![-1, 1], vec![1, -1]],
+                Rule::MintermCount
+            ),
+            false
+        );
+        assert_eq!(
+            check(
+                vec![vec![1, 0, 0], vec![0, 1, 0], vec![0, 0, 1]],
+                Rule::Unate
+            ),
+            false
+        );
+        // x3 is unate, so the Cube x1 x3 is removed
+        let cubes = vec![vec![1, 1, 0], vec![-1, 0, 0], vec![1, -1, 0], vec![1, 0, 1]];
+        assert_eq!(check(cubes, Rule::UnateReduction), true);
+        // x1 x2 + x1' + x2' is a tautology on its own
+        let cubes = vec![
+            vec![1, 1, 0, 0],
+            vec![-1, 0, 0, 0],
+            vec![0, -1, 0, 0],
+            vec![0, 0, 1, -1],
+            vec![0, 0, -1, 1],
+        ];
+        assert_eq!(check(cubes, Rule::Components), true);
+
+        // The rules give the same results as splitting alone
+        let cubes: Vec<Vec<i32>> = (0..27)
+            .map(|i| vec![i % 3 - 1, i / 3 % 3 - 1, i / 9 - 1])
+            .collect();
+        for mask in (0u32..1 << 26).step_by(4099) {
+            let cubelist: CubeList = cubes
+                .iter()
+                .enumerate()
+                .filter(|(i, _)| mask >> i & 1 == 1)
+                .map(|(_, cube)| Cube::from(cube.clone()))
+                .collect();
+            let cubelist = CubeList(cubelist.0, 3);
+            assert_eq!(cubelist.is_tautology(), cubelist.minterm_count() == 8);
+        }
+    }
+
+    #[test]
+    fn components() {
+        let cubelist = CubeList::from(vec![
+            vec![1, 0, 0, 1, 0, 0],
+            vec![0, -1, 1, 0, 0, 0],
+            vec![0, 0, 0, -1, 0, 0],
+            vec![0, 0, 0, 0, 0, 1],
+            vec![0, 1, 0, 0, 0, 0],
+        ]);
+        let components = cubelist.components();
+        let sizes: Vec<(usize, usize)> = components
+            .iter()
+            .map(|component| (component.num_var(), component.len()))
+            .collect();
+        assert_eq!(sizes, vec![(6, 2), (6, 2), (6, 1)]);
+        assert_eq!(components[1].support(), vec![2, 3]);
+        assert_eq!(CubeList::new(3).components().len(), 0);
+
+        // A wide sparse cover, the OR of 6 functions of 3 variables each
+        let mut cubelist = CubeList::new(18);
+        for i in 0..6 {
+            let vars = [3 * i + 1, 3 * i + 2, 3 * i + 3];
+            let mut cube = Cube::new(18);
+            cube.set_literal(vars[0], Literal::Positive);
+            cube.set_literal(vars[1], Literal::Negative);
+            cubelist.add_cube(cube);
+            let mut cube = Cube::new(18);
+            cube.set_literal(vars[1], Literal::Positive);
+            cube.set_literal(vars[2], Literal::Positive);
+            cubelist.add_cube(cube);
+        }
+        assert_eq!(cubelist.components().len(), 6);
+        let complement = cubelist.complement();
+        assert_eq!(cubelist.and(&complement).is_empty(), true);
+        assert_eq!(cubelist.or(&complement).is_tautology(), true);
+        assert_eq!(complement.0, cubelist.complement_parallel().0);
+    }
+
+    #[test]
+    fn split_var() {
+        // x1 has 3 literals and is unbalanced, x2 has 2 balanced literals,
+        // x3 is unate with 4 literals. MostBinate also takes the unate
+        // variables after the first binate one
+        let cubelist = CubeList::from(vec![
+            vec![1, 1, 1],
+            vec![1, 0, 1],
+            vec![-1, -1, 1],
+            vec![0, 0, 1],
+        ]);
+        assert_eq!(cubelist.split_var(SplitStrategy::MostBinate), 3);
+        assert_eq!(cubelist.split_var(SplitStrategy::MostBinateBalanced), 1);
+        assert_eq!(cubelist.split_var(SplitStrategy::MostBalanced), 2);
+        assert_eq!(cubelist.split_var(SplitStrategy::FirstBinate), 1);
+        assert_eq!(cubelist.split_var(SplitStrategy::MaxLiterals), 3);
+        let random = cubelist.split_var(SplitStrategy::Random { seed: 3 });
+        assert_eq!(random == 1 || random == 2, true);
+        assert_eq!(
+            random,
+            cubelist.split_var(SplitStrategy::Random { seed: 3 })
+        );
+        assert_eq!(CubeList::new(2).split_var(SplitStrategy::FirstBinate), 0);
+
+        // x1 and x2 tie with 4 unbalanced literals, so MostBinate chooses
+        // the most balanced binate variable x3, as get_most_unate_var did
+        let cubelist = CubeList::from(vec![
+            vec![1, -1, 1],
+            vec![1, -1, -1],
+            vec![1, -1, 0],
+            vec![-1, 1, 0],
+        ]);
+        assert_eq!(cubelist.get_most_unate_var(), 3);
+        assert_eq!(SplitStrategy::default(), SplitStrategy::MostBinate);
+        assert_eq!(cubelist.split_var(SplitStrategy::MostBinateBalanced), 1);
+
+        // Every strategy gives the same results
+        let cubelist = CubeList::from(vec![
+            vec![1, 1, 0, 0],
+            vec![-1, 0, 1, 0],
+            vec![0, -1, -1, 1],
+            vec![0, 0, 1, -1],
+            vec![-1, -1, 0, 0],
+        ]);
+        let complement = cubelist.complement();
+        for name in SplitStrategy::NAMES.iter() {
+            let options = UrpOptions {
+                split: name.parse().unwrap(),
+                ..UrpOptions::default()
+            };
+            assert_eq!(
+                cubelist.complement_with(&options).unwrap() == complement,
+                true
+            );
+            let tautology = cubelist.or(&complement);
+            assert_eq!(tautology.is_tautology_with(&options).unwrap(), true);
+            assert_eq!(cubelist.is_tautology_with(&options).unwrap(), false);
+        }
+    }
+
+    #[test]
+    fn limits() {
+        use crate::error::Limit;
+        use crate::options::{CancelToken, Limits};
+        use std::time::Duration;
+
+        // The XOR of 6 variables, whose complement needs 6 levels of recursion
+        let mut cubelist = CubeList::new(6);
+        for i in 0..64u32 {
+            if i.count_ones() % 2 == 1 {
+                let cube: Vec<i32> = (0..6)
+                    .map(|j| if i >> j & 1 == 1 { 1 } else { -1 })
+                    .collect();
+                cubelist.add_cube(Cube::from(cube));
+            }
+        }
+        // All the 64 minterms, a tautology
+        let minterms = cubelist.or(&cubelist.complement());
+        let limited = |limits: Limits| {
+            let options = UrpOptions {
+                limits,
+                ..UrpOptions::default()
+            };
+            (
+                cubelist.complement_with(&options),
+                minterms.is_tautology_with(&options),
+            )
+        };
+        match limited(Limits {
+            max_depth: Some(3),
+            ..Limits::default()
+        }) {
+            (
+                Err(UrpError::LimitExceeded(Limit::Depth(3))),
+                Err(UrpError::LimitExceeded(Limit::Depth(3))),
+            ) => {}
+            result => panic!("expected the depth limit, found {:?}", result),
+        }
+        match limited(Limits {
+            max_cubes: Some(20),
+            ..Limits::default()
+        }) {
+            (Err(UrpError::LimitExceeded(Limit::Cubes(20))), Ok(true)) => {}
+            result => panic!("expected the cube limit, found {:?}", result),
+        }
+        match limited(Limits {
+            timeout: Some(Duration::from_secs(0)),
+            ..Limits::default()
+        }) {
+            (
+                Err(UrpError::LimitExceeded(Limit::Timeout(_))),
+                Err(UrpError::LimitExceeded(Limit::Timeout(_))),
+            ) => {}
+            result => panic!("expected the timeout, found {:?}", result),
+        }
+        let (complement, tautology) = limited(Limits {
+            max_depth: Some(6),
+            max_cubes: Some(32),
+            timeout: Some(Duration::from_secs(60)),
+        });
+        assert_eq!(complement.unwrap().len(), 32);
+        assert_eq!(tautology.unwrap(), true);
+
+        let cancel = CancelToken::new();
+        cancel.cancel();
+        let options = UrpOptions {
+            parallel: true,
+            cancel: Some(&cancel),
+            ..UrpOptions::default()
+        };
+        match minterms.is_tautology_with(&options) {
+            Err(UrpError::LimitExceeded(Limit::Cancelled)) => {}
+            result => panic!("expected a cancellation, found {:?}", result),
+        }
+    }
+
+    #[test]
+    fn trace() {
+        use crate::trace::{Operation, Step, Tracer};
+
+        let cubelist = CubeList::from(vec![
+            vec![1, 1, 0, 0],
+            vec![-1, 0, 1, 0],
+            vec![0, -1, -1, 0],
+            vec![0, 0, 0, 1],
+        ]);
+        let tracer = Tracer::new();
+        let options = UrpOptions {
+            trace: Some(&tracer),
+            ..UrpOptions::default()
+        };
+        let complement = cubelist.complement_with(&options).unwrap();
+        assert_eq!(complement, cubelist.complement());
+        let nodes = tracer.nodes();
+        assert_eq!(nodes[0].parent, None);
+        assert_eq!(nodes[0].cubes, 4);
+        assert_eq!(nodes[0].step, Some(Step::Rule(Rule::Components)));
+        // The second component is the single Cube d
+        assert_eq!(nodes[1].step, Some(Step::Rule(Rule::Split)));
+        assert_eq!(nodes.last().unwrap().step, Some(Step::SingleCube));
+        for node in &nodes[1..] {
+            assert_eq!(node.operation, Operation::Complement);
+            let parent = &nodes[node.parent.unwrap()];
+            assert_eq!(parent.depth + 1 >= node.depth, true);
+            assert_eq!(parent.elapsed >= node.elapsed, true);
+        }
+
+        tracer.clear();
+        let tautology = cubelist.is_tautology_with(&options).unwrap();
+        assert_eq!(tautology, false);
+        let nodes = tracer.nodes();
+        assert_eq!(nodes[0].operation, Operation::Tautology);
+        assert_eq!(nodes[0].step, Some(Step::Rule(Rule::UnateReduction)));
+        // The cover without the Cube d has too few minterms
+        assert_eq!((nodes[1].parent, nodes[1].cubes), (Some(0), 3));
+        assert_eq!(nodes[1].step, Some(Step::Rule(Rule::MintermCount)));
+        assert_eq!(nodes.len(), 2);
+    }
+
+    /// Returns a strategy of the split strategies, built from their names
+    /// so that a new strategy is covered too. The random strategy gets a
+    /// random seed
+    fn split_strategy() -> impl Strategy<Value = SplitStrategy> {
+        (
+            proptest::sample::select(SplitStrategy::NAMES.to_vec()),
+            any::<u64>(),
+        )
+            .prop_map(|(name, seed)| match name.parse().unwrap() {
+                SplitStrategy::Random { .. } => SplitStrategy::Random { seed },
+                split => split,
+            })
+    }
+
+    proptest! {
+        #[test]
+        fn complement_is_negation(cubelist in oracle::any_cubelist(), split in split_strategy()) {
+            let expected: Vec<bool> = truth_table(&cubelist).iter().map(|value| !value).collect();
+            prop_assert_eq!(truth_table(&cubelist.complement()), expected.clone());
+            let options = UrpOptions {
+                split,
+                ..UrpOptions::default()
+            };
+            let complement = cubelist.complement_with(&options).unwrap();
+            prop_assert_eq!(truth_table(&complement), expected);
+        }
+
+        #[test]
+        fn and_or_xor_match_truth_tables((cubelist_f, cubelist_g) in oracle::cubelist_pair()) {
+            let (table_f, table_g) = (truth_table(&cubelist_f), truth_table(&cubelist_g));
+            let expected = |op: fn(bool, bool) -> bool| -> Vec<bool> {
+                table_f.iter().zip(&table_g).map(|(f, g)| op(*f, *g)).collect()
+            };
+            prop_assert_eq!(truth_table(&cubelist_f.and(&cubelist_g)), expected(|f, g| f && g));
+            prop_assert_eq!(truth_table(&cubelist_f.or(&cubelist_g)), expected(|f, g| f || g));
+            prop_assert_eq!(truth_table(&cubelist_f.xor(&cubelist_g)), expected(|f, g| f != g));
+            prop_assert_eq!(cubelist_f == cubelist_g, table_f == table_g);
+        }
+
+        #[test]
+        fn is_tautology_matches_truth_table(cubelist in oracle::any_cubelist()) {
+            let table = truth_table(&cubelist);
+            prop_assert_eq!(cubelist.is_tautology(), table.iter().all(|value| *value));
+            prop_assert_eq!(cubelist.is_tautology_parallel(), table.iter().all(|value| *value));
+        }
+
+        #[test]
+        fn is_tautology_of_planted_tautologies(cubelist in (1..=8usize).prop_flat_map(oracle::cubelist)) {
+            // Adding the missing minterms makes a tautology, and leaving
+            // one of them out does not
+            let num_var = cubelist.num_var();
+            let missing: Vec<Cube> = truth_table(&cubelist)
+                .iter()
+                .enumerate()
+                .filter(|(_, value)| !**value)
+                .map(|(minterm, _)| minterm_cube(num_var, minterm))
+                .collect();
+            let mut tautology = cubelist.clone();
+            for cube in &missing {
+                tautology.add_cube(cube.clone());
+            }
+            prop_assert_eq!(tautology.is_tautology(), true);
+            if let Some((_, rest)) = missing.split_first() {
+                let mut cubelist = cubelist;
+                for cube in rest {
+                    cubelist.add_cube(cube.clone());
+                }
+                prop_assert_eq!(cubelist.is_tautology(), false);
+            }
+        }
+
+        #[test]
+        fn cofactor_is_shannon_expansion(
+            (cubelist, var_num) in (1..=MAX_VARS).prop_flat_map(|num_var| {
+                (oracle::cubelist(num_var), 1..=num_var)
+            })
+        ) {
+            let (pos_cofactor, neg_cofactor) = cubelist.cofactor(var_num);
+            let (table, pos_table, neg_table) = (
+                truth_table(&cubelist),
+                truth_table(&pos_cofactor),
+                truth_table(&neg_cofactor),
+            );
+            let bit = 1 << (var_num - 1);
+            for minterm in 0..table.len() {
+                // The cofactors do not depend on the variable
+                prop_assert_eq!(pos_table[minterm], pos_table[minterm ^ bit]);
+                prop_assert_eq!(neg_table[minterm], neg_table[minterm ^ bit]);
+                let expected = if minterm & bit != 0 {
+                    pos_table[minterm]
+                } else {
+                    neg_table[minterm]
+                };
+                prop_assert_eq!(table[minterm], expected, "minterm {}", minterm);
+            }
+        }
+    }
 }
@@ -0,0 +1,98 @@
+use std::fmt;
+use std::io;
+use std::time::Duration;
+
+/// Limit is the limit of a complement or a tautology check that was exceeded
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Limit {
+    /// The recursion went deeper than this number of levels
+    Depth(usize),
+    /// A complement had more than this number of Cubes
+    Cubes(usize),
+    /// The operation ran for longer than this
+    Timeout(Duration),
+    /// The operation was cancelled with its CancelToken
+    Cancelled,
+}
+
+impl fmt::Display for Limit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Limit::Depth(depth) => write!(f, "recursion deeper than {} levels", depth),
+            Limit::Cubes(cubes) => write!(f, "more than {} cubes", cubes),
+            Limit::Timeout(timeout) => write!(f, "ran for more than {:?}", timeout),
+            Limit::Cancelled => write!(f, "cancelled"),
+        }
+    }
+}
+
+/// UrpError represents the errors returned while reading, writing
+/// and operating on boolean functions
+#[derive(Debug)]
+pub enum UrpError {
+    /// A file could not be read or written
+    Io { path: String, error: io::Error },
+    /// A file or command could not be parsed
+    ///
+    /// source is the name of the file being parsed, and line and
+    /// column are counted from 1
+    Parse {
+        source: String,
+        line: usize,
+        column: usize,
+        message: String,
+    },
+    /// No boolean function is stored with the given number
+    UnknownFunction(u32),
+    /// The number of variables does not match the expected number
+    VarCountMismatch { expected: usize, found: usize },
+    /// The variable number is 0, out of range or repeated
+    InvalidVariable(usize),
+    /// A complement or a tautology check exceeded one of its limits
+    /// or was cancelled, and was stopped
+    LimitExceeded(Limit),
+    /// The condition of an assert command in a script does not hold
+    AssertionFailed {
+        source: String,
+        line: usize,
+        message: String,
+    },
+}
+
+impl fmt::Display for UrpError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            UrpError::Io { path, error } => write!(f, "{}: {}", path, error),
+            UrpError::Parse {
+                source,
+                line,
+                column,
+                message,
+            } => write!(f, "{}:{}:{}: {}", source, line, column, message),
+            UrpError::UnknownFunction(function) => {
+                write!(f, "Boolean function {} not found", function)
+            }
+            UrpError::VarCountMismatch { expected, found } => write!(
+                f,
+                "expected {} variables, found {} variables",
+                expected, found
+            ),
+            UrpError::InvalidVariable(var_num) => write!(f, "variable {} is not valid", var_num),
+            UrpError::LimitExceeded(limit) => write!(f, "limit exceeded: {}", limit),
+            UrpError::AssertionFailed {
+                source,
+                line,
+                message,
+            } => write!(f, "{}:{}: assertion failed: {}", source, line, message),
+        }
+    }
+}
+
+impl std::error::Error for UrpError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            UrpError::Io { error, .. } => Some(error),
+            _ => None,
+        }
+    }
+}
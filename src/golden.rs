@@ -0,0 +1,179 @@
+//! Regression tests of command scripts against expected outputs
+//!
+//! Each `cmd*.txt` script of a directory is run in its own sandbox
+//! directory, which holds a copy of the files of the directory. The
+//! `.pcn` files in `expected/<script name>/` are the outputs the script
+//! must produce, and they are compared to the produced files by
+//! equivalence of the functions rather than by their text. Files named
+//! like an expected output are not copied, so a stale file in the
+//! directory never stands in for an output the script did not write.
+
+use super::cubelist::CubeList;
+use super::error::UrpError;
+use super::session::Session;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process;
+
+/// The directory, inside the tested directory, of the expected outputs
+pub const EXPECTED_DIR: &str = "expected";
+
+/// Returns the paths of the files of a directory, in sorted order,
+/// that satisfy the filter
+fn files(dir: &Path, filter: impl Fn(&str) -> bool) -> Result<Vec<PathBuf>, UrpError> {
+    let mut files: Vec<_> = fs::read_dir(dir)
+        .map_err(|error| UrpError::Io {
+            path: dir.display().to_string(),
+            error,
+        })?
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.is_file())
+        .filter(|path| {
+            path.file_name()
+                .is_some_and(|name| filter(&name.to_string_lossy()))
+        })
+        .collect();
+    files.sort();
+    Ok(files)
+}
+
+/// This function runs every `cmd*.txt` script of a directory and compares
+/// the `.pcn` files it writes to the expected ones
+///
+/// Returns the name of each script with the problems found, which are
+/// empty if the script passed
+pub fn run_dir(dir: &Path) -> Result<Vec<(String, Vec<String>)>, UrpError> {
+    let scripts = files(dir, |name| {
+        name.starts_with("cmd") && name.ends_with(".txt")
+    })?;
+    let mut results = Vec::new();
+    for script in scripts {
+        let name = script.file_name().unwrap().to_string_lossy().into_owned();
+        let sandbox = sandbox_dir(&name);
+        let problems = run_script(dir, &script, &sandbox);
+        // The sandbox is only a copy, so failing to remove it is not a problem of the script
+        let _ = fs::remove_dir_all(&sandbox);
+        results.push((name, problems?));
+    }
+    Ok(results)
+}
+
+/// Returns a new sandbox directory path for the script
+fn sandbox_dir(script: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("urp-test-{}-{}", process::id(), script))
+}
+
+/// Runs a single script in the sandbox and returns the problems found
+fn run_script(dir: &Path, script: &Path, sandbox: &Path) -> Result<Vec<String>, UrpError> {
+    let io_error = |path: &Path| {
+        let path = path.display().to_string();
+        move |error| UrpError::Io { path, error }
+    };
+    let stem = script.file_stem().unwrap();
+    let expected_dir = dir.join(EXPECTED_DIR).join(stem);
+    let outputs = if expected_dir.is_dir() {
+        files(&expected_dir, |name| name.ends_with(".pcn"))?
+    } else {
+        Vec::new()
+    };
+    let is_output = |name: &str| outputs.iter().any(|output| output.ends_with(name));
+
+    fs::create_dir_all(sandbox).map_err(io_error(sandbox))?;
+    for file in files(dir, |name| !is_output(name))? {
+        let copy = sandbox.join(file.file_name().unwrap());
+        fs::copy(&file, &copy).map_err(io_error(&file))?;
+    }
+
+    let mut problems = Vec::new();
+    let source = script.display().to_string();
+    let contents = fs::read_to_string(script).map_err(io_error(script))?;
+    let mut session = Session::with_output(Box::new(io::sink()));
+    session.set_dir(sandbox);
+    for (line_num, line) in contents.lines().enumerate() {
+        match session.handle_command(line, &source, line_num + 1) {
+            Ok(true) => {}
+            Ok(false) => break,
+            Err(error @ UrpError::AssertionFailed { .. }) => {
+                problems.push(error.to_string());
+                return Ok(problems);
+            }
+            Err(error) => problems.push(error.to_string()),
+        }
+    }
+    if let Err(error) = session.finish() {
+        problems.push(error.to_string());
+    }
+
+    if !expected_dir.is_dir() {
+        problems.push(format!("{} does not exist", expected_dir.display()));
+        return Ok(problems);
+    }
+    for expected in outputs {
+        let name = expected.file_name().unwrap();
+        let produced = sandbox.join(name);
+        if !produced.exists() {
+            problems.push(format!("{} was not written", name.to_string_lossy()));
+            continue;
+        }
+        let expected = CubeList::read_from_file(&expected.to_string_lossy());
+        let produced = CubeList::read_from_file(&produced.to_string_lossy());
+        match (expected, produced) {
+            (Ok(expected), Ok(produced)) => {
+                if expected != produced {
+                    problems.push(format!(
+                        "{} is not equivalent to the expected output",
+                        name.to_string_lossy()
+                    ));
+                }
+            }
+            (Err(error), _) | (_, Err(error)) => problems.push(error.to_string()),
+        }
+    }
+    Ok(problems)
+}
+
+#[cfg(test)]
+#[allow(clippy::bool_assert_comparison)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn scripts() {
+        let dir = std::env::temp_dir().join(format!("urp_golden_{}", process::id()));
+        let expected = dir.join(EXPECTED_DIR);
+        fs::create_dir_all(expected.join("cmd1")).unwrap();
+        fs::create_dir_all(expected.join("cmd2")).unwrap();
+        fs::create_dir_all(expected.join("cmd4")).unwrap();
+        fs::write(dir.join("1.pcn"), "2\n1\n1 1\n").unwrap();
+        // A stale output, which is the expected output of cmd1 and cmd4
+        fs::write(dir.join("0.pcn"), "2\n1\n1 -1\n").unwrap();
+        fs::write(dir.join("cmd1.txt"), "r 1\n! 0 1\np 0\nq\n").unwrap();
+        fs::write(dir.join("cmd2.txt"), "r 1\n! 2 1\np 2\n").unwrap();
+        fs::write(dir.join("cmd3.txt"), "r 1\n").unwrap();
+        fs::write(dir.join("cmd4.txt"), "r 1\nq\n").unwrap();
+        // The same function as the complement of 1, written differently
+        fs::write(expected.join("cmd1/0.pcn"), "2\n2\n1 -1\n2 -1 2\n").unwrap();
+        fs::write(expected.join("cmd2/2.pcn"), "2\n1\n1 2\n").unwrap();
+        fs::write(expected.join("cmd2/0.pcn"), "2\n1\n1 1\n").unwrap();
+        fs::write(expected.join("cmd4/0.pcn"), "2\n1\n1 -1\n").unwrap();
+        let results = super::run_dir(&dir).unwrap();
+        let written = fs::read_dir(&dir).unwrap().count();
+        fs::remove_dir_all(&dir).unwrap();
+
+        // The scripts ran in sandboxes, so no file was added to the directory
+        assert_eq!(written, 7);
+        assert_eq!(results.len(), 4);
+        assert_eq!(results[0], ("cmd1.txt".to_string(), vec![]));
+        assert_eq!(
+            results[1].1,
+            vec![
+                "0.pcn was not written".to_string(),
+                "2.pcn is not equivalent to the expected output".to_string()
+            ]
+        );
+        assert_eq!(results[2].1.len(), 1);
+        // The stale 0.pcn was not copied, so a script that writes nothing fails
+        assert_eq!(results[3].1, vec!["0.pcn was not written".to_string()]);
+    }
+}
@@ -0,0 +1,35 @@
+//! Boolean calculator engine using the Unate Recursive Paradigm
+//!
+//! Boolean functions are stored as a CubeList, a list of product terms
+//! represented as Cubes in Positional Cube Notation. The command
+//! interpreter used by the urp binary is available as a Session
+
+pub mod cache;
+mod cover;
+pub mod cube;
+pub mod cubelist;
+pub mod error;
+pub mod golden;
+pub mod options;
+#[cfg(test)]
+mod oracle;
+mod parse;
+pub mod pcn;
+pub mod pla;
+pub mod random;
+mod rng;
+pub mod rules;
+pub mod session;
+pub mod stream;
+pub mod trace;
+
+pub use cache::ComputedTable;
+pub use cube::{Cube, Literal};
+pub use cubelist::CubeList;
+pub use error::{Limit, UrpError};
+pub use options::{CancelToken, Limits, SplitStrategy, UrpOptions};
+pub use random::RandomOptions;
+pub use rules::{Rule, RuleCounters};
+pub use session::Session;
+pub use stream::{CubeReader, CubeWriter};
+pub use trace::Tracer;
@@ -1,135 +1,479 @@
-mod cube;
-mod cubelist;
+#[cfg(feature = "repl")]
+mod repl;
 
-use cubelist::CubeList;
-use std::collections::HashMap;
 use std::env;
-use std::io::{self, BufRead, Read};
+use std::io::{self, BufRead, IsTerminal};
+use std::path::{Path, PathBuf};
+use std::process;
+use std::str::FromStr;
+use urp::{golden, pcn, random, CubeList, RandomOptions, Session, UrpError};
 
-fn main() {
-    let mut index = HashMap::<u32, CubeList>::new();
-    if env::args().collect::<Vec<String>>().len() > 1 {
-        // Read commands from file
-        let mut file =
-            std::fs::File::open(env::args().collect::<Vec<String>>()[1].as_str()).unwrap();
-        let mut contents = String::new();
-        file.read_to_string(&mut contents);
-        for line in contents.lines() {
-            if !handle_command(&mut index, line.to_string()) {
-                return;
+/// Exit status when a tautology or equivalence check fails
+const EXIT_CHECK_FAILED: i32 = 1;
+
+/// Exit status when the arguments, a file or a command is invalid
+const EXIT_ERROR: i32 = 2;
+
+const USAGE: &str = "Usage: urp [options] [command]
+
+Commands:
+  run <script>              Runs the commands in the script file
+  taut <file>               Checks if the function in the file is a tautology
+  eq <file1> <file2>        Checks if the functions in the files are equivalent
+  min <file> [-o <output>]  Minimizes the function in the file
+  gen <vars> <cubes> [-o <output>]
+                            Generates a random function with the number of
+                            variables and cubes
+  check [dir]               Validates the .pcn files in the directory
+  test [dir]                Runs the cmd*.txt scripts of the directory and compares
+                            their outputs to the .pcn files in expected/<script>/
+  help                      Prints this message
+
+With no command, the commands are read from the standard input.
+Functions are read from .pcn files, or from PLA files if the name ends in .pla
+
+Options:
+  -d, --dir <dir>           Directory of the .pcn files used by the commands
+  -o, --output <file>       Output file of min and gen, printed to the standard output if missing
+  -q, --quiet               Prints only errors, even with --verbose
+  -v, --verbose             Prints each command of a script before running it
+  -j, --parallel            Complements and checks tautologies with parallel tasks
+
+Options of gen:
+  --density <d>             Probability of a literal for each variable of a cube, 0.5 by default
+  --seed <n>                Seed of the random numbers, 0 by default
+  --unate                   Generates a unate function
+  --support <n>             Uses only n of the variables
+  --taut                    Generates a tautology";
+
+/// Options holds the flags and the remaining arguments of the command line
+struct Options {
+    dir: PathBuf,
+    output: Option<String>,
+    quiet: bool,
+    verbose: bool,
+    parallel: bool,
+    density: f64,
+    seed: u64,
+    random: RandomOptions,
+    args: Vec<String>,
+}
+
+impl Options {
+    /// Parses the command line arguments, without the program name
+    fn parse(args: &[String]) -> Result<Options, String> {
+        let mut options = Options {
+            dir: PathBuf::new(),
+            output: None,
+            quiet: false,
+            verbose: false,
+            parallel: false,
+            density: 0.5,
+            seed: 0,
+            random: RandomOptions::default(),
+            args: vec![],
+        };
+        let mut args = args.iter();
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "-d" | "--dir" => {
+                    let dir = args
+                        .next()
+                        .ok_or(format!("Expected a directory after {}", arg))?;
+                    options.dir = PathBuf::from(dir);
+                }
+                "-o" | "--output" => {
+                    let file = args
+                        .next()
+                        .ok_or(format!("Expected a file after {}", arg))?;
+                    options.output = Some(file.clone());
+                }
+                "-q" | "--quiet" => options.quiet = true,
+                "-v" | "--verbose" => options.verbose = true,
+                "-j" | "--parallel" => options.parallel = true,
+                "--density" => options.density = number(args.next(), arg)?,
+                "--seed" => options.seed = number(args.next(), arg)?,
+                "--support" => options.random.support = Some(number(args.next(), arg)?),
+                "--unate" => options.random.unate = true,
+                "--taut" => options.random.tautology = true,
+                "-h" | "--help" => options.args.push("help".to_string()),
+                "--check" => options.args.push("check".to_string()),
+                _ if arg.starts_with('-') && arg.len() > 1 => {
+                    return Err(format!("Unknown option {}", arg))
+                }
+                _ => options.args.push(arg.clone()),
             }
         }
-    } else {
-        // Read commands from standard input
-        let stdin = io::stdin();
-        for line in stdin.lock().lines() {
-            if !handle_command(&mut index, line.unwrap()) {
-                return;
-            }
+        // Nothing but errors is printed with --quiet, even with --verbose
+        options.verbose &= !options.quiet;
+        Ok(options)
+    }
+
+    /// Returns the argument at position i parsed as a number, or an error
+    /// naming it if it is missing or invalid
+    fn number<T: FromStr>(&self, i: usize, name: &str) -> Result<T, String> {
+        let arg = self.arg(i, name)?;
+        arg.parse()
+            .map_err(|_| format!("Expected {}, found {}", name, arg))
+    }
+
+    /// Returns the argument at position i, or an error naming it if it is missing
+    fn arg(&self, i: usize, name: &str) -> Result<&str, String> {
+        self.args
+            .get(i)
+            .map(|arg| arg.as_str())
+            .ok_or(format!("Expected {} after {}", name, self.args[0]))
+    }
+
+    /// Returns a new Session that follows the options
+    fn session(&self) -> Session {
+        let mut session = if self.quiet {
+            Session::with_output(Box::new(io::sink()))
+        } else {
+            Session::new()
+        };
+        session.set_dir(&self.dir);
+        session.set_parallel(self.parallel);
+        session
+    }
+
+    /// Reads the function in a .pcn or .pla file, relative to the directory
+    fn read_function(&self, file: &str) -> Result<CubeList, UrpError> {
+        let path = self.dir.join(file).to_string_lossy().into_owned();
+        if file.ends_with(".pla") {
+            CubeList::read_pla(&path)
+        } else {
+            CubeList::read_from_file(&path)
         }
     }
 }
 
-/// This function handles a single command
-/// It returns false if the command is a quit command, else it returns true
-fn handle_command(index: &mut HashMap<u32, CubeList>, line: String) -> bool {
-    let contents: Vec<&str> = line.split_whitespace().collect();
-    let command: char = contents[0].chars().collect::<Vec<char>>()[0];
-    match command {
-        'q' => return false,
-        'p' => {
-            // Prints the function into a file
-            if contents.len() < 2 {
-                println!("Expected 1 argument");
-                return true;
-            }
-            let function: u32 = contents[1].parse::<u32>().expect("Expected an number");
-            if let Some(cubelist_1) = index.get(&function) {
-                cubelist_1.write_to_file(format!("{}.pcn", function).as_str());
-            }
+/// Parses the value that follows an option as a number
+fn number<T: FromStr>(value: Option<&String>, option: &str) -> Result<T, String> {
+    value
+        .and_then(|value| value.parse().ok())
+        .ok_or(format!("Expected a number after {}", option))
+}
+
+fn main() {
+    let args = env::args().skip(1).collect::<Vec<String>>();
+    let options = match Options::parse(&args) {
+        Ok(options) => options,
+        Err(message) => {
+            eprintln!("{}\n\n{}", message, USAGE);
+            process::exit(EXIT_ERROR);
         }
-        't' => {
-            // Checks if a function is a tautology
-            if contents.len() < 2 {
-                println!("Expected 1 argument");
-                return true;
-            }
-            let function: u32 = contents[1].parse::<u32>().expect("Expected an number");
-            if let Some(cubelist_1) = index.get(&function) {
-                if cubelist_1.is_tautology() {
-                    println!("{} is a tautology", function);
-                } else {
-                    println!("{} is not a tautology", function);
-                }
-            }
+    };
+    let result = match options.args.first().map(|arg| arg.as_str()) {
+        None => Ok(run_stdin(&options)),
+        Some("help") => {
+            println!("{}", USAGE);
+            Ok(0)
         }
-        'r' => {
-            // Reads the function from a file
-            if contents.len() < 2 {
-                println!("Expected 1 argument");
-                return true;
-            }
-            let function: u32 = contents[1].parse::<u32>().expect("Expected an number");
-            index.insert(
-                function,
-                CubeList::read_from_file(format!("{}.pcn", function).as_str()),
-            );
-        }
-        '!' => {
-            // Perform Complement of a function
-            if contents.len() < 3 {
-                println!("Expected 2 arguments");
-                return true;
+        Some("run") => options
+            .arg(1, "a script file")
+            .map(|script| run_script(&options, script)),
+        Some("taut") => options.arg(1, "a file").map(|file| taut(&options, file)),
+        Some("eq") => options.arg(1, "two files").and_then(|file1| {
+            options
+                .arg(2, "two files")
+                .map(|file2| eq(&options, file1, file2))
+        }),
+        Some("min") => options.arg(1, "a file").map(|file| min(&options, file)),
+        Some("gen") => options
+            .number(1, "a number of variables")
+            .and_then(|num_var| {
+                options
+                    .number(2, "a number of cubes")
+                    .map(|num_cubes| gen(&options, num_var, num_cubes))
+            }),
+        Some("check") => Ok(check(&options, options.args.get(1).map_or(".", |dir| dir))),
+        Some("test") => Ok(test(&options, options.args.get(1).map_or(".", |dir| dir))),
+        // A single argument is a script file, as in earlier versions
+        Some(script) => Ok(run_script(&options, script)),
+    };
+    match result {
+        Ok(status) => process::exit(status),
+        Err(message) => {
+            eprintln!("{}\n\n{}", message, USAGE);
+            process::exit(EXIT_ERROR);
+        }
+    }
+}
+
+/// Runs the commands in a script file and returns the exit status,
+/// which is nonzero if any command failed
+///
+/// A failed assert command stops the script with EXIT_CHECK_FAILED, and a
+/// t command that finds a function not to be a tautology makes the status
+/// EXIT_CHECK_FAILED unless another command failed
+fn run_script(options: &Options, script: &str) -> i32 {
+    let contents = match std::fs::read_to_string(script) {
+        Ok(contents) => contents,
+        Err(error) => {
+            eprintln!("{}: {}", script, error);
+            return EXIT_ERROR;
+        }
+    };
+    let mut session = options.session();
+    let mut status = 0;
+    for (line_num, line) in contents.lines().enumerate() {
+        if options.verbose {
+            println!("> {}", line);
+        }
+        match session.handle_command(line, script, line_num + 1) {
+            Ok(true) => {}
+            Ok(false) => break,
+            // A failed assertion stops the script
+            Err(error @ UrpError::AssertionFailed { .. }) => {
+                eprintln!("{}", error);
+                return EXIT_CHECK_FAILED;
             }
-            let output: u32 = contents[1].parse::<u32>().expect("Expected an number");
-            let input: u32 = contents[2].parse::<u32>().expect("Expected an number");
-            if let Some(cubelist_1) = index.get(&input) {
-                index.insert(output, cubelist_1.complement());
-            } else {
-                println!("Boolean function {} not found", input);
+            Err(error) => {
+                eprintln!("{}", error);
+                status = EXIT_ERROR;
             }
         }
-        '&' => {
-            // Perform AND of two functions
-            if contents.len() < 4 {
-                println!("Expected 3 arguments");
-                return true;
+    }
+    if let Err(error) = session.finish() {
+        eprintln!("{}", error);
+        status = EXIT_ERROR;
+    }
+    if status == 0 && session.failed_checks() > 0 {
+        status = EXIT_CHECK_FAILED;
+    }
+    status
+}
+
+/// Runs the commands read from the standard input and returns the exit status
+///
+/// When the standard input is a terminal, the commands are read
+/// with line editing, history and tab completion
+fn run_stdin(options: &Options) -> i32 {
+    let mut session = options.session();
+    let stdin = io::stdin();
+    if cfg!(feature = "repl") && stdin.is_terminal() {
+        #[cfg(feature = "repl")]
+        repl::run(session);
+        return 0;
+    }
+    for (line_num, line) in stdin.lock().lines().enumerate() {
+        let line = match line {
+            Ok(line) => line,
+            Err(error) => {
+                eprintln!("<stdin>: {}", error);
+                return EXIT_ERROR;
             }
-            let output: u32 = contents[1].parse::<u32>().expect("Expected an number");
-            let input1: u32 = contents[2].parse::<u32>().expect("Expected an number");
-            let input2: u32 = contents[3].parse::<u32>().expect("Expected an number");
-            if let Some(cubelist_1) = index.get(&input1) {
-                if let Some(cubelist_2) = index.get(&input2) {
-                    index.insert(output, cubelist_1.and(cubelist_2));
-                } else {
-                    println!("Boolean function {} not found", input2);
-                }
+        };
+        match session.handle_command(line.as_str(), "<stdin>", line_num + 1) {
+            Ok(true) => {}
+            Ok(false) => break,
+            Err(error) => eprintln!("{}", error),
+        }
+    }
+    if let Err(error) = session.finish() {
+        eprintln!("{}", error);
+    }
+    0
+}
+
+/// Checks if the function in a file is a tautology
+fn taut(options: &Options, file: &str) -> i32 {
+    let cubelist = match options.read_function(file) {
+        Ok(cubelist) => cubelist,
+        Err(error) => {
+            eprintln!("{}", error);
+            return EXIT_ERROR;
+        }
+    };
+    let tautology = if options.parallel {
+        cubelist.is_tautology_parallel()
+    } else {
+        cubelist.is_tautology()
+    };
+    if !options.quiet {
+        if tautology {
+            println!("{} is a tautology", file);
+        } else {
+            println!("{} is not a tautology", file);
+        }
+    }
+    if tautology {
+        0
+    } else {
+        EXIT_CHECK_FAILED
+    }
+}
+
+/// Checks if the functions in two files are equivalent
+fn eq(options: &Options, file1: &str, file2: &str) -> i32 {
+    let cubelists = options
+        .read_function(file1)
+        .and_then(|cubelist_1| Ok((cubelist_1, options.read_function(file2)?)));
+    let (cubelist_1, cubelist_2) = match cubelists {
+        Ok(cubelists) => cubelists,
+        Err(error) => {
+            eprintln!("{}", error);
+            return EXIT_ERROR;
+        }
+    };
+    let equivalent = cubelist_1 == cubelist_2;
+    if !options.quiet {
+        if equivalent {
+            println!("{} and {} are equivalent", file1, file2);
+        } else {
+            println!("{} and {} are not equivalent", file1, file2);
+        }
+    }
+    if equivalent {
+        0
+    } else {
+        EXIT_CHECK_FAILED
+    }
+}
+
+/// Minimizes the function in a file and writes it in the same format,
+/// to the output file if one is given or else to the standard output
+fn min(options: &Options, file: &str) -> i32 {
+    let cubelist = match options.read_function(file) {
+        Ok(cubelist) => cubelist,
+        Err(error) => {
+            eprintln!("{}", error);
+            return EXIT_ERROR;
+        }
+    };
+    let (minimized, optimal) = cubelist.minimize_exact();
+    if options.verbose {
+        eprintln!(
+            "{} cubes minimized to {} cubes{}",
+            cubelist.len(),
+            minimized.len(),
+            if optimal {
+                ""
             } else {
-                println!("Boolean function {} not found", input1);
+                ", not proven to be a minimum"
             }
+        );
+    }
+    let pla = options.output.as_deref().unwrap_or(file).ends_with(".pla");
+    let result = match &options.output {
+        Some(output) if pla => minimized.write_pla(output),
+        Some(output) => minimized.write_to_file(output),
+        None if pla => {
+            print!("{}", urp::pla::to_string(&minimized));
+            Ok(())
+        }
+        None => {
+            print!("{}", minimized);
+            Ok(())
         }
-        '+' => {
-            // Perform OR of two functions
-            if contents.len() < 4 {
-                println!("Expected 3 arguments");
-                return true;
+    };
+    match result {
+        Ok(()) => 0,
+        Err(error) => {
+            eprintln!("{}", error);
+            EXIT_ERROR
+        }
+    }
+}
+
+/// Generates a random function and writes it to the output file, in the
+/// PLA format if its name ends in .pla, or else to the standard output
+fn gen(options: &Options, num_var: usize, num_cubes: usize) -> i32 {
+    if let Err(message) = random::check_size(num_var, num_cubes) {
+        eprintln!("{}", message);
+        return EXIT_ERROR;
+    }
+    let cubelist = CubeList::random_with(
+        num_var,
+        num_cubes,
+        options.density,
+        options.seed,
+        &options.random,
+    );
+    let result = match &options.output {
+        Some(output) if output.ends_with(".pla") => cubelist.write_pla(output),
+        Some(output) => cubelist.write_to_file(output),
+        None => {
+            print!("{}", cubelist);
+            Ok(())
+        }
+    };
+    match result {
+        Ok(()) => 0,
+        Err(error) => {
+            eprintln!("{}", error);
+            EXIT_ERROR
+        }
+    }
+}
+
+/// Validates the .pcn files of a directory
+fn check(options: &Options, dir: &str) -> i32 {
+    let results = match pcn::check_dir(Path::new(dir)) {
+        Ok(results) => results,
+        Err(error) => {
+            eprintln!("{}", error);
+            return EXIT_ERROR;
+        }
+    };
+    let mut num_invalid = 0;
+    for (_, errors) in &results {
+        for error in errors {
+            println!("{}", error);
+        }
+        if !errors.is_empty() {
+            num_invalid += 1;
+        }
+    }
+    if !options.quiet {
+        println!(
+            "{} files checked, {} with problems",
+            results.len(),
+            num_invalid
+        );
+    }
+    if num_invalid > 0 {
+        EXIT_CHECK_FAILED
+    } else {
+        0
+    }
+}
+
+/// Runs the scripts of a directory and compares their outputs to the expected ones
+fn test(options: &Options, dir: &str) -> i32 {
+    let results = match golden::run_dir(Path::new(dir)) {
+        Ok(results) => results,
+        Err(error) => {
+            eprintln!("{}", error);
+            return EXIT_ERROR;
+        }
+    };
+    let mut num_failed = 0;
+    for (script, problems) in &results {
+        if problems.is_empty() {
+            if !options.quiet {
+                println!("{} ... ok", script);
             }
-            let output: u32 = contents[1].parse::<u32>().expect("Expected an number");
-            let input1: u32 = contents[2].parse::<u32>().expect("Expected an number");
-            let input2: u32 = contents[3].parse::<u32>().expect("Expected an number");
-            if let Some(cubelist_1) = index.get(&input1) {
-                if let Some(cubelist_2) = index.get(&input2) {
-                    index.insert(output, cubelist_1.or(cubelist_2));
-                } else {
-                    println!("Boolean function {} not found", input2);
-                }
-            } else {
-                println!("Boolean function {} not found", input1);
+        } else {
+            num_failed += 1;
+            println!("{} ... FAILED", script);
+            for problem in problems {
+                println!("    {}", problem);
             }
         }
-        _ => {
-            println!("Invalid command");
-        }
     }
-    true
+    if !options.quiet {
+        println!(
+            "{} scripts run, {} passed, {} failed",
+            results.len(),
+            results.len() - num_failed,
+            num_failed
+        );
+    }
+    if num_failed > 0 {
+        EXIT_CHECK_FAILED
+    } else {
+        0
+    }
 }
@@ -0,0 +1,275 @@
+//! Options of the URP recursion used by complement and is_tautology
+
+use super::cache::ComputedTable;
+use super::error::{Limit, UrpError};
+use super::rules::{Rule, RuleCounters};
+use super::trace::Tracer;
+use std::fmt;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// SplitStrategy selects the variable on which the URP recursion splits
+/// a cover into its two cofactors
+///
+/// Except for MostBinate and MaxLiterals, only binate variables are chosen
+/// while the cover has some. For unate covers, each strategy chooses among
+/// the variables of the cover
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SplitStrategy {
+    /// The rule of get_most_unate_var: the variable with the most literals
+    /// among the first binate variable and the variables after it. If two
+    /// of them had the same number of literals, it is the binate variable
+    /// with the smallest difference between its positive and negative
+    /// literals instead
+    #[default]
+    MostBinate,
+    /// The binate variable with the most literals, ties broken by the
+    /// smallest difference between its positive and negative literals
+    MostBinateBalanced,
+    /// The binate variable with the smallest difference between its
+    /// positive and negative literals, ties broken by the most literals
+    MostBalanced,
+    /// The binate variable with the smallest number
+    FirstBinate,
+    /// The variable with the most literals, binate or not
+    MaxLiterals,
+    /// A binate variable chosen at random. The choice depends only on the
+    /// seed and on the Cubes of the cover, so it is repeatable
+    Random { seed: u64 },
+}
+
+impl SplitStrategy {
+    /// The names of the strategies, as parsed by from_str
+    pub const NAMES: [&'static str; 6] = [
+        "binate",
+        "binate-balanced",
+        "balanced",
+        "first",
+        "literals",
+        "random",
+    ];
+}
+
+impl FromStr for SplitStrategy {
+    type Err = String;
+
+    /// Parses the name of a strategy. The random strategy has
+    /// the seed 0, or the seed given after a colon as in random:42
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        match name {
+            "binate" => Ok(SplitStrategy::MostBinate),
+            "binate-balanced" => Ok(SplitStrategy::MostBinateBalanced),
+            "balanced" => Ok(SplitStrategy::MostBalanced),
+            "first" => Ok(SplitStrategy::FirstBinate),
+            "literals" => Ok(SplitStrategy::MaxLiterals),
+            "random" => Ok(SplitStrategy::Random { seed: 0 }),
+            _ => match name.strip_prefix("random:").map(|seed| seed.parse()) {
+                Some(Ok(seed)) => Ok(SplitStrategy::Random { seed }),
+                _ => Err(format!(
+                    "Unknown split strategy {}, expected one of {}",
+                    name,
+                    SplitStrategy::NAMES.join(", ")
+                )),
+            },
+        }
+    }
+}
+
+impl fmt::Display for SplitStrategy {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SplitStrategy::MostBinate => write!(f, "binate"),
+            SplitStrategy::MostBinateBalanced => write!(f, "binate-balanced"),
+            SplitStrategy::MostBalanced => write!(f, "balanced"),
+            SplitStrategy::FirstBinate => write!(f, "first"),
+            SplitStrategy::MaxLiterals => write!(f, "literals"),
+            SplitStrategy::Random { seed } => write!(f, "random:{}", seed),
+        }
+    }
+}
+
+/// UrpOptions holds the options of a complement or a tautology check,
+/// which are passed to complement_with and is_tautology_with
+///
+/// The default options run the recursion sequentially, without a
+/// cache, without counting the rules, with the MostBinate strategy,
+/// without limits and without tracing
+/// ```
+/// use urp::{ComputedTable, CubeList, UrpOptions};
+///
+/// let cache = ComputedTable::new();
+/// let options = UrpOptions {
+///     parallel: true,
+///     cache: Some(&cache),
+///     ..UrpOptions::default()
+/// };
+/// let cubelist = CubeList::from(vec![vec![1, 0], vec![-1, 0]]);
+/// assert!(cubelist.is_tautology_with(&options)?);
+/// # Ok::<(), urp::UrpError>(())
+/// ```
+#[derive(Clone, Copy, Default)]
+pub struct UrpOptions<'a> {
+    /// Handle the cofactors of the first levels of the recursion in
+    /// parallel, when the parallel feature is enabled
+    pub parallel: bool,
+    /// Reuse and store the results for sub-covers in this cache
+    pub cache: Option<&'a ComputedTable>,
+    /// Count the rules applied by is_tautology in these counters
+    pub rules: Option<&'a RuleCounters>,
+    /// Split the covers on the variable chosen by this strategy
+    pub split: SplitStrategy,
+    /// Stop with an error once one of these limits is exceeded
+    pub limits: Limits,
+    /// Stop with an error once this token is cancelled
+    pub cancel: Option<&'a CancelToken>,
+    /// Record the tree of covers met by the recursion in this tracer
+    pub trace: Option<&'a Tracer>,
+}
+
+impl<'a> UrpOptions<'a> {
+    /// Adds 1 to the counter of the rule, if the rules are counted
+    pub(crate) fn count(&self, rule: Rule) {
+        if let Some(rules) = self.rules {
+            rules.count(rule);
+        }
+    }
+
+    /// Returns the deadline of an operation that starts now
+    pub(crate) fn deadline(&self) -> Option<Instant> {
+        self.limits.timeout.map(|timeout| Instant::now() + timeout)
+    }
+
+    /// Returns an error if the recursion at the given depth exceeds a limit,
+    /// runs past the deadline or was cancelled
+    pub(crate) fn check(&self, depth: usize, deadline: Option<Instant>) -> Result<(), UrpError> {
+        if self.cancel.is_some_and(|cancel| cancel.is_cancelled()) {
+            return Err(UrpError::LimitExceeded(Limit::Cancelled));
+        }
+        if let Some(max_depth) = self.limits.max_depth {
+            if depth > max_depth {
+                return Err(UrpError::LimitExceeded(Limit::Depth(max_depth)));
+            }
+        }
+        if let (Some(deadline), Some(timeout)) = (deadline, self.limits.timeout) {
+            if Instant::now() >= deadline {
+                return Err(UrpError::LimitExceeded(Limit::Timeout(timeout)));
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns an error if a complement has more Cubes than the limit
+    pub(crate) fn check_cubes(&self, cubes: usize) -> Result<(), UrpError> {
+        match self.limits.max_cubes {
+            Some(max_cubes) if cubes > max_cubes => {
+                Err(UrpError::LimitExceeded(Limit::Cubes(max_cubes)))
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Limits bounds the resources used by a complement or a tautology
+/// check. Each limit is off when it is None, as in the default Limits
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Limits {
+    /// The largest number of levels of the recursion
+    pub max_depth: Option<usize>,
+    /// The largest number of Cubes of the complements, including the
+    /// complements of the sub-covers
+    pub max_cubes: Option<usize>,
+    /// The longest time the operation can run for
+    pub timeout: Option<Duration>,
+}
+
+/// CancelToken stops the operations that were given the token, from
+/// another thread. Clones of a token share the same state
+#[derive(Debug, Clone, Default)]
+pub struct CancelToken(Arc<AtomicBool>);
+
+impl CancelToken {
+    /// Returns a new token that is not cancelled
+    pub fn new() -> Self {
+        CancelToken::default()
+    }
+
+    /// Cancels the operations that use the token
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    /// Returns true if the token was cancelled
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+
+    /// Makes the token usable again after a cancellation
+    pub fn reset(&self) {
+        self.0.store(false, Ordering::Relaxed);
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::bool_assert_comparison)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn split_strategy() {
+        for name in SplitStrategy::NAMES.iter() {
+            let strategy: SplitStrategy = name.parse().unwrap();
+            assert_eq!(strategy.to_string().starts_with(name), true);
+        }
+        assert_eq!(
+            "random:42".parse::<SplitStrategy>(),
+            Ok(SplitStrategy::Random { seed: 42 })
+        );
+        assert_eq!("random:x".parse::<SplitStrategy>().is_err(), true);
+        assert_eq!("best".parse::<SplitStrategy>().is_err(), true);
+        assert_eq!(SplitStrategy::default(), SplitStrategy::MostBinate);
+    }
+
+    #[test]
+    fn limits() {
+        let cancel = CancelToken::new();
+        let options = UrpOptions {
+            limits: Limits {
+                max_depth: Some(3),
+                max_cubes: Some(10),
+                timeout: Some(Duration::from_secs(60)),
+            },
+            cancel: Some(&cancel),
+            ..UrpOptions::default()
+        };
+        let deadline = options.deadline();
+        assert_eq!(options.check(3, deadline).is_ok(), true);
+        match options.check(4, deadline) {
+            Err(UrpError::LimitExceeded(Limit::Depth(3))) => {}
+            result => panic!("expected the depth limit, found {:?}", result),
+        }
+        assert_eq!(options.check_cubes(10).is_ok(), true);
+        assert_eq!(options.check_cubes(11).is_err(), true);
+        match options.check(0, Some(Instant::now() - Duration::from_secs(1))) {
+            Err(UrpError::LimitExceeded(Limit::Timeout(_))) => {}
+            result => panic!("expected the timeout, found {:?}", result),
+        }
+        // A zero timeout is already over when the operation starts
+        let zero = UrpOptions {
+            limits: Limits {
+                timeout: Some(Duration::ZERO),
+                ..Limits::default()
+            },
+            ..UrpOptions::default()
+        };
+        assert_eq!(zero.check(0, zero.deadline()).is_err(), true);
+        cancel.clone().cancel();
+        match options.check(0, deadline) {
+            Err(UrpError::LimitExceeded(Limit::Cancelled)) => {}
+            result => panic!("expected a cancellation, found {:?}", result),
+        }
+        cancel.reset();
+        assert_eq!(options.check(0, deadline).is_ok(), true);
+    }
+}
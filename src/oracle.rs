@@ -0,0 +1,74 @@
+//! A truth table oracle and generators of random covers for the property tests
+//!
+//! The truth table of a function of N variables holds its value on each of
+//! its 2^N minterms, where bit i - 1 of the number of a minterm is the value
+//! of the variable i. The generated covers have at most MAX_VARS variables,
+//! so that their truth tables stay small, and shrink to covers with fewer
+//! Cubes and fewer literals when a property fails.
+
+use super::cube::{Cube, Literal};
+use super::cubelist::CubeList;
+use proptest::collection::vec;
+use proptest::prelude::*;
+
+/// The largest number of variables of the generated covers
+pub(crate) const MAX_VARS: usize = 12;
+
+/// The largest number of Cubes of the generated covers
+pub(crate) const MAX_CUBES: usize = 12;
+
+/// Returns the value of the Cube on the minterm
+pub(crate) fn cube_value(cube: &Cube, minterm: usize) -> bool {
+    (1..=cube.len()).all(|i| match cube.get_literal(i).unwrap() {
+        Literal::Positive => minterm >> (i - 1) & 1 == 1,
+        Literal::Negative => minterm >> (i - 1) & 1 == 0,
+        Literal::Dontcare => true,
+    })
+}
+
+/// Returns the truth table of the function
+pub(crate) fn truth_table(cubelist: &CubeList) -> Vec<bool> {
+    (0..1 << cubelist.num_var())
+        .map(|minterm| cubelist.iter().any(|cube| cube_value(cube, minterm)))
+        .collect()
+}
+
+/// Returns the Cube of a minterm of num_var variables
+pub(crate) fn minterm_cube(num_var: usize, minterm: usize) -> Cube {
+    let literals = (0..num_var)
+        .map(|i| if minterm >> i & 1 == 1 { 1 } else { -1 })
+        .collect::<Vec<i32>>();
+    Cube::from(literals)
+}
+
+/// Returns a strategy of Cubes of num_var variables. A variable is more
+/// often without a literal, and literals shrink to no literal
+pub(crate) fn cube(num_var: usize) -> impl Strategy<Value = Cube> {
+    vec(
+        prop_oneof![2 => Just(0), 1 => Just(1), 1 => Just(-1)],
+        num_var,
+    )
+    .prop_map(Cube::from)
+}
+
+/// Returns a strategy of covers of num_var variables with at most
+/// MAX_CUBES Cubes
+pub(crate) fn cubelist(num_var: usize) -> impl Strategy<Value = CubeList> {
+    vec(cube(num_var), 0..=MAX_CUBES).prop_map(move |cubes| {
+        let mut cubelist = CubeList::new(num_var);
+        for cube in cubes {
+            cubelist.add_cube(cube);
+        }
+        cubelist
+    })
+}
+
+/// Returns a strategy of covers of 1 to MAX_VARS variables
+pub(crate) fn any_cubelist() -> impl Strategy<Value = CubeList> {
+    (1..=MAX_VARS).prop_flat_map(cubelist)
+}
+
+/// Returns a strategy of pairs of covers with the same number of variables
+pub(crate) fn cubelist_pair() -> impl Strategy<Value = (CubeList, CubeList)> {
+    (1..=MAX_VARS).prop_flat_map(|num_var| (cubelist(num_var), cubelist(num_var)))
+}
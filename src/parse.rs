@@ -0,0 +1,19 @@
+//! Helpers shared by the parsers of PCN files, PLA files and scripts
+
+/// Returns the words of a line along with their column numbers, counted from 1
+pub(crate) fn tokenize(line: &str) -> impl Iterator<Item = (usize, &str)> {
+    line.split_whitespace()
+        .map(move |word| (word.as_ptr() as usize - line.as_ptr() as usize + 1, word))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn tokenize_columns() {
+        let words: Vec<(usize, &str)> = tokenize("  2 1\t-3 ").collect();
+        assert_eq!(words, vec![(3, "2"), (5, "1"), (7, "-3")]);
+        assert_eq!(tokenize("   ").next(), None);
+    }
+}
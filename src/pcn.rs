@@ -0,0 +1,336 @@
+use super::cube::{Cube, Literal, MAX_NUM_VAR};
+use super::cubelist::CubeList;
+use super::error::UrpError;
+use super::parse::tokenize;
+use std::fs;
+use std::path::Path;
+
+/// This function parses the contents of a PCN file and returns the boolean
+/// function. The format is described in CubeList::read_from_file
+///
+/// Every problem found in the contents is reported, along with its line and
+/// column. The problems checked are
+/// * a missing or invalid number of variables or number of cubes, or
+///   more variables than cube::MAX_NUM_VAR
+/// * a literal count that does not match the literals on the line
+/// * literals that are not integers, 0 or larger than the number of variables
+/// * repeated literals and variables present as both literals, like `3 -3`
+/// * fewer cube lines than declared, and any text after the last cube
+pub fn parse(source: &str, contents: &str) -> Result<CubeList, Vec<UrpError>> {
+    let mut errors = Vec::new();
+    let mut error = |line: usize, column: usize, message: String| {
+        errors.push(UrpError::Parse {
+            source: source.to_string(),
+            line,
+            column,
+            message,
+        })
+    };
+    let mut parser = Parser::new();
+    let mut cubes = Vec::new();
+    for (i, line) in contents.lines().enumerate() {
+        cubes.extend(parser.line(i + 1, line, &mut error));
+    }
+    parser.finish(&mut error);
+
+    if errors.is_empty() {
+        let mut cubelist = CubeList::new(parser.num_var().unwrap_or(0));
+        for cube in cubes {
+            cubelist.add_cube(cube);
+        }
+        Ok(cubelist)
+    } else {
+        Err(errors)
+    }
+}
+
+/// Parser reads a PCN file one line at a time, so that the cubes can be
+/// used as soon as their line is read. The problems found are reported
+/// to the error function along with their line and column
+pub(crate) struct Parser {
+    num_var: Option<usize>,
+    num_cubes: usize,
+    found: usize,
+    last_line: usize,
+}
+
+impl Parser {
+    /// Returns a parser at the start of a file
+    pub(crate) fn new() -> Self {
+        Parser {
+            num_var: None,
+            num_cubes: 0,
+            found: 0,
+            last_line: 0,
+        }
+    }
+
+    /// Returns the number of variables, once the first line was parsed
+    pub(crate) fn num_var(&self) -> Option<usize> {
+        self.num_var
+    }
+
+    /// Parses the line with the given number, which follows the line
+    /// parsed last, and returns its Cube if it is a cube line
+    pub(crate) fn line(
+        &mut self,
+        line_num: usize,
+        line: &str,
+        error: &mut dyn FnMut(usize, usize, String),
+    ) -> Option<Cube> {
+        self.last_line = line_num;
+        match line_num {
+            1 => {
+                self.num_var =
+                    parse_header(line_num, line, "number of variables", MAX_NUM_VAR, error);
+                None
+            }
+            2 => {
+                self.num_cubes =
+                    parse_header(line_num, line, "number of cubes", usize::MAX, error).unwrap_or(0);
+                None
+            }
+            _ if self.found < self.num_cubes => {
+                self.found += 1;
+                parse_cube(line_num, line, self.num_var, error)
+            }
+            _ => {
+                if !line.trim().is_empty() {
+                    error(
+                        line_num,
+                        1,
+                        "Unexpected text after the last cube".to_string(),
+                    );
+                }
+                None
+            }
+        }
+    }
+
+    /// Reports the problems found at the end of the file
+    pub(crate) fn finish(&self, error: &mut dyn FnMut(usize, usize, String)) {
+        if self.last_line < 1 {
+            error(1, 1, "Expected the number of variables".to_string());
+        }
+        if self.last_line < 2 {
+            error(1, 1, "Expected the number of cubes".to_string());
+        } else if self.found < self.num_cubes {
+            error(
+                self.last_line + 1,
+                1,
+                format!("Expected {} cubes, found {}", self.num_cubes, self.found),
+            );
+        }
+    }
+}
+
+/// Parses the line of a cube and returns the Cube, or None if the line has
+/// no literal count or the number of variables is not known. The literals
+/// are checked against each other before the Cube is built, so that an
+/// invalid variable never makes it grow
+fn parse_cube(
+    line_num: usize,
+    line: &str,
+    num_var: Option<usize>,
+    error: &mut dyn FnMut(usize, usize, String),
+) -> Option<Cube> {
+    let mut words = tokenize(line);
+    let (count_column, count) = match words.next() {
+        Some(word) => word,
+        None => {
+            error(line_num, 1, "Expected the number of literals".to_string());
+            return None;
+        }
+    };
+    let mut literals: Vec<i32> = Vec::new();
+    for (column, word) in words {
+        let literal = match word.parse::<i32>() {
+            Ok(literal) => literal,
+            Err(_) => {
+                error(
+                    line_num,
+                    column,
+                    format!("Expected an integer value, found {}", word),
+                );
+                continue;
+            }
+        };
+        let var_num = literal.unsigned_abs() as usize;
+        if var_num == 0 || num_var.is_some_and(|num_var| var_num > num_var) {
+            error(
+                line_num,
+                column,
+                format!("variable {} is not valid", literal),
+            );
+        } else if literals.contains(&literal) {
+            error(line_num, column, format!("literal {} is repeated", literal));
+        } else if literals
+            .iter()
+            .any(|found| found.unsigned_abs() == literal.unsigned_abs())
+        {
+            error(
+                line_num,
+                column,
+                format!("variable {} is present as both literals", var_num),
+            );
+        }
+        literals.push(literal);
+    }
+    match count.parse::<usize>() {
+        Ok(count) if count != literals.len() => error(
+            line_num,
+            count_column,
+            format!("Declared {} literals, found {}", count, literals.len()),
+        ),
+        Ok(_) => {}
+        Err(_) => error(
+            line_num,
+            count_column,
+            format!("Expected the number of literals, found {}", count),
+        ),
+    }
+    let mut cube = Cube::new(num_var?);
+    for literal in literals {
+        let value = if literal > 0 {
+            Literal::Positive
+        } else {
+            Literal::Negative
+        };
+        cube.set_literal(literal.unsigned_abs() as usize, value);
+    }
+    Some(cube)
+}
+
+/// Parses a header line that contains a single number, at most max
+fn parse_header(
+    line_num: usize,
+    line: &str,
+    name: &str,
+    max: usize,
+    error: &mut dyn FnMut(usize, usize, String),
+) -> Option<usize> {
+    let mut words = tokenize(line);
+    let value = match words.next() {
+        Some((column, word)) => match word.parse::<usize>() {
+            Ok(value) if value > max => {
+                error(
+                    line_num,
+                    column,
+                    format!("The {} {} is larger than {}", name, value, max),
+                );
+                None
+            }
+            Ok(value) => Some(value),
+            Err(_) => {
+                error(
+                    line_num,
+                    column,
+                    format!("Expected the {}, found {}", name, word),
+                );
+                None
+            }
+        },
+        None => {
+            error(line_num, 1, format!("Expected the {}", name));
+            None
+        }
+    };
+    for (column, word) in words {
+        error(line_num, column, format!("Unexpected {}", word));
+    }
+    value
+}
+
+/// This function validates every .pcn file in the directory
+///
+/// It returns the name of each file checked along with
+/// the problems found in that file
+pub fn check_dir(dir: &Path) -> Result<Vec<(String, Vec<UrpError>)>, UrpError> {
+    let io_error = |error| UrpError::Io {
+        path: dir.display().to_string(),
+        error,
+    };
+    let mut files: Vec<_> = fs::read_dir(dir)
+        .map_err(io_error)?
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.extension().is_some_and(|ext| ext == "pcn"))
+        .collect();
+    files.sort();
+    let mut results = Vec::new();
+    for path in files {
+        let name = path.display().to_string();
+        let errors = match fs::read_to_string(&path) {
+            Ok(contents) => parse(&name, &contents).err().unwrap_or_default(),
+            Err(error) => vec![UrpError::Io {
+                path: name.clone(),
+                error,
+            }],
+        };
+        results.push((name, errors));
+    }
+    Ok(results)
+}
+
+#[cfg(test)]
+#[allow(clippy::bool_assert_comparison)]
+mod test {
+    use super::*;
+
+    /// Returns the line and column of every problem found in contents
+    fn problems(contents: &str) -> Vec<(usize, usize)> {
+        parse("test.pcn", contents)
+            .err()
+            .unwrap_or_default()
+            .iter()
+            .map(|error| match error {
+                UrpError::Parse { line, column, .. } => (*line, *column),
+                _ => panic!("expected a parse error, found {:?}", error),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn valid_file() {
+        let cubelist = parse("test.pcn", "3\n2\n2 1 -3\n0\n").unwrap();
+        assert_eq!(cubelist.len(), 2);
+        assert_eq!(cubelist.contains_cube(&Cube::from(vec![1, 0, -1])), true);
+        assert_eq!(cubelist.contains_cube(&Cube::from(vec![0, 0, 0])), true);
+        assert_eq!(parse("test.pcn", "3\n0\n").unwrap().num_var(), 3);
+    }
+
+    #[test]
+    fn literal_count() {
+        assert_eq!(problems("3\n1\n3 1 2\n"), vec![(3, 1)]);
+        assert_eq!(problems("3\n1\nx 1 2\n"), vec![(3, 1)]);
+    }
+
+    #[test]
+    fn invalid_literals() {
+        assert_eq!(problems("3\n1\n2 1 -3 \n"), vec![]);
+        assert_eq!(problems("3\n1\n2 3 -3\n"), vec![(3, 5)]);
+        assert_eq!(problems("3\n1\n2 2 2\n"), vec![(3, 5)]);
+        assert_eq!(problems("3\n1\n2 0 4\n"), vec![(3, 3), (3, 5)]);
+        assert_eq!(problems("3\n1\n1 a\n"), vec![(3, 3), (3, 1)]);
+        assert_eq!(problems("x\n1\n1 -2147483648\n"), vec![(1, 1)]);
+        assert_eq!(
+            problems("x\n1\n2 2147483647 -2147483647\n"),
+            vec![(1, 1), (3, 14)]
+        );
+        // Without a valid number of variables, the literals are still checked
+        assert_eq!(
+            problems("x\n1\n2 2000000000 -2000000000\n"),
+            vec![(1, 1), (3, 14)]
+        );
+    }
+
+    #[test]
+    fn missing_and_trailing_lines() {
+        assert_eq!(problems("3\n3\n1 1\n1 2\n"), vec![(5, 1)]);
+        assert_eq!(problems("3\n1\n1 1\n1 2\n\n"), vec![(4, 1)]);
+        assert_eq!(problems("3 4\n1\n1 1\n"), vec![(1, 3)]);
+        assert_eq!(problems("1000000000000\n1\n1 1\n"), vec![(1, 1)]);
+        assert_eq!(problems("65537\n1000000\n0\n"), vec![(1, 1), (4, 1)]);
+        assert_eq!(problems("65536\n1\n1 65536\n"), vec![]);
+        assert_eq!(problems(""), vec![(1, 1), (1, 1)]);
+    }
+}
@@ -0,0 +1,287 @@
+use super::cube::{Cube, MAX_NUM_VAR};
+use super::cubelist::CubeList;
+use super::error::UrpError;
+use super::parse::tokenize;
+
+/// This function parses the contents of a single output PLA file
+/// and returns the ON-set of the function
+///
+/// The file contains
+/// * `.i N`, the number of inputs. If it is missing, it is taken
+///   from the length of the first cube
+/// * `.o 1`, the number of outputs, which must be 1
+/// * an optional `.p P`, the number of cubes
+/// * one line for each cube, with one character for each input as in
+///   Cube::to_pla_string, followed by the output. Only the cubes with
+///   output 1 are part of the ON-set
+/// * an optional `.e` that ends the file
+///
+/// Lines starting with # are comments, and other lines starting
+/// with . like `.ilb` and `.type` are ignored
+pub fn parse(source: &str, contents: &str) -> Result<CubeList, Vec<UrpError>> {
+    let mut errors = Vec::new();
+    let mut error = |line: usize, column: usize, message: String| {
+        errors.push(UrpError::Parse {
+            source: source.to_string(),
+            line,
+            column,
+            message,
+        })
+    };
+    let mut parser = Parser::new();
+    let mut cubes = Vec::new();
+    for (i, line) in contents.lines().enumerate() {
+        cubes.extend(parser.line(i + 1, line, &mut error));
+        if parser.ended() {
+            break;
+        }
+    }
+    parser.finish(&mut error);
+
+    if errors.is_empty() {
+        let mut cubelist = CubeList::new(parser.num_var().unwrap_or(0));
+        for cube in cubes {
+            cubelist.add_cube(cube);
+        }
+        Ok(cubelist)
+    } else {
+        Err(errors)
+    }
+}
+
+/// Parser reads a PLA file one line at a time, so that the cubes can be
+/// used as soon as their line is read. The problems found are reported
+/// to the error function along with their line and column
+pub(crate) struct Parser {
+    num_var: Option<usize>,
+    num_cubes: Option<(usize, usize)>,
+    found: usize,
+    last_line: usize,
+    ended: bool,
+}
+
+impl Parser {
+    /// Returns a parser at the start of a file
+    pub(crate) fn new() -> Self {
+        Parser {
+            num_var: None,
+            num_cubes: None,
+            found: 0,
+            last_line: 0,
+            ended: false,
+        }
+    }
+
+    /// Returns the number of variables, once .i or the first cube was parsed
+    pub(crate) fn num_var(&self) -> Option<usize> {
+        self.num_var
+    }
+
+    /// Returns true once the .e line that ends the file was parsed
+    pub(crate) fn ended(&self) -> bool {
+        self.ended
+    }
+
+    /// Parses the line with the given number, which follows the line
+    /// parsed last, and returns its Cube if it is a cube of the ON-set
+    pub(crate) fn line(
+        &mut self,
+        line_num: usize,
+        line: &str,
+        error: &mut dyn FnMut(usize, usize, String),
+    ) -> Option<Cube> {
+        self.last_line = line_num;
+        let words: Vec<(usize, &str)> = tokenize(line).collect();
+        let (column, first) = *words.first()?;
+        if first.starts_with('#') {
+            return None;
+        }
+        if first.starts_with('.') {
+            let value = words.get(1).map(|(column, word)| {
+                word.parse::<usize>().map_err(|_| {
+                    (
+                        *column,
+                        format!("Expected a number after {}, found {}", first, word),
+                    )
+                })
+            });
+            let value = match (first, value) {
+                (".e", _) | (".end", _) => {
+                    self.ended = true;
+                    return None;
+                }
+                (".i", Some(Ok(value))) | (".o", Some(Ok(value))) | (".p", Some(Ok(value))) => {
+                    value
+                }
+                (".i", Some(Err((column, message))))
+                | (".o", Some(Err((column, message))))
+                | (".p", Some(Err((column, message)))) => {
+                    error(line_num, column, message);
+                    return None;
+                }
+                (".i", None) | (".o", None) | (".p", None) => {
+                    error(
+                        line_num,
+                        column + first.len(),
+                        format!("Expected a number after {}", first),
+                    );
+                    return None;
+                }
+                _ => return None,
+            };
+            match first {
+                ".i" if value > MAX_NUM_VAR => error(
+                    line_num,
+                    words[1].0,
+                    format!("Expected at most {} inputs, found {}", MAX_NUM_VAR, value),
+                ),
+                ".i" => self.num_var = Some(value),
+                ".o" if value != 1 => error(
+                    line_num,
+                    column,
+                    format!(
+                        "Only single output PLA files are supported, found {}",
+                        value
+                    ),
+                ),
+                ".p" => self.num_cubes = Some((line_num, value)),
+                _ => {}
+            }
+            return None;
+        }
+
+        // A cube line is the inputs followed by the output, with or without a space
+        let (inputs, output, output_column) = match words.as_slice() {
+            [(_, inputs), (output_column, output)] => (*inputs, *output, *output_column),
+            [(_, word)] if word.chars().nth(1).is_some() => {
+                let split = word.char_indices().last().map_or(0, |(split, _)| split);
+                (&word[..split], &word[split..], column + split)
+            }
+            _ => {
+                error(
+                    line_num,
+                    column,
+                    "Expected the inputs and the output of a cube".to_string(),
+                );
+                return None;
+            }
+        };
+        self.found += 1;
+        let cube = match Cube::from_pla_str(inputs) {
+            Ok(cube) => cube,
+            Err(UrpError::Parse {
+                column: offset,
+                message,
+                ..
+            }) => {
+                error(line_num, column + offset - 1, message);
+                return None;
+            }
+            Err(_) => return None,
+        };
+        match self.num_var {
+            Some(num_var) if num_var != cube.len() => {
+                error(
+                    line_num,
+                    column,
+                    format!("Expected {} inputs, found {}", num_var, cube.len()),
+                );
+                return None;
+            }
+            Some(_) => {}
+            None => self.num_var = Some(cube.len()),
+        }
+        match output {
+            "1" | "4" => return Some(cube),
+            "0" | "-" | "~" | "2" | "3" => {}
+            _ => error(
+                line_num,
+                output_column,
+                format!("Expected the output 0, 1 or -, found {}", output),
+            ),
+        }
+        None
+    }
+
+    /// Reports the problems found at the end of the file
+    pub(crate) fn finish(&self, error: &mut dyn FnMut(usize, usize, String)) {
+        if let Some((line_num, num_cubes)) = self.num_cubes {
+            if num_cubes != self.found {
+                error(
+                    line_num,
+                    1,
+                    format!("Declared {} cubes, found {}", num_cubes, self.found),
+                );
+            }
+        }
+        if self.num_var.is_none() && self.found == 0 {
+            error(self.last_line + 1, 1, "Expected .i or a cube".to_string());
+        }
+    }
+}
+
+/// This function returns the boolean function in the single output
+/// PLA format read by parse
+pub fn to_string(cubelist: &CubeList) -> String {
+    let mut contents = format!(".i {}\n.o 1\n.p {}\n", cubelist.num_var(), cubelist.len());
+    for cube in cubelist {
+        contents.push_str(&cube.to_pla_string());
+        contents.push_str(" 1\n");
+    }
+    contents.push_str(".e\n");
+    contents
+}
+
+#[cfg(test)]
+#[allow(clippy::bool_assert_comparison)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn parse_and_write() {
+        let contents = "# example\n.i 3\n.o 1\n.ilb a b c\n.p 3\n1-0 1\n-11 0\n01- 1\n.e\n";
+        let cubelist = parse("test.pla", contents).unwrap();
+        assert_eq!(cubelist.num_var(), 3);
+        assert_eq!(cubelist.len(), 2);
+        assert_eq!(cubelist.contains_cube(&Cube::from(vec![1, 0, -1])), true);
+        assert_eq!(cubelist.contains_cube(&Cube::from(vec![-1, 1, 0])), true);
+
+        let written = to_string(&cubelist);
+        assert_eq!(written, ".i 3\n.o 1\n.p 2\n1-0 1\n01- 1\n.e\n");
+        assert_eq!(parse("test.pla", &written).unwrap(), cubelist);
+    }
+
+    #[test]
+    fn without_header() {
+        let cubelist = parse("test.pla", "1-01\n0-11\n").unwrap();
+        assert_eq!(cubelist.num_var(), 3);
+        assert_eq!(cubelist.len(), 2);
+    }
+
+    #[test]
+    fn errors() {
+        let problems = |contents: &str| -> Vec<(usize, usize)> {
+            parse("test.pla", contents)
+                .err()
+                .unwrap_or_default()
+                .iter()
+                .map(|error| match error {
+                    UrpError::Parse { line, column, .. } => (*line, *column),
+                    _ => panic!("expected a parse error, found {:?}", error),
+                })
+                .collect()
+        };
+        assert_eq!(problems(".i 3\n.o 2\n"), vec![(2, 1)]);
+        assert_eq!(problems(".i 65537\n.o 1\n"), vec![(1, 4), (3, 1)]);
+        let inputs = "1".repeat(MAX_NUM_VAR + 1);
+        assert_eq!(problems(&format!("{} 1\n", inputs)), vec![(1, 65537)]);
+        assert_eq!(problems(".i 3\n1x0 1\n"), vec![(2, 2)]);
+        assert_eq!(problems(".i 3\n10 1\n"), vec![(2, 1)]);
+        assert_eq!(problems(".i 3\n.p 2\n100 1\n"), vec![(2, 1)]);
+        assert_eq!(problems(".i 3\n100 x\n"), vec![(2, 5)]);
+        // The output is the last character, which may not be ASCII
+        assert_eq!(problems(".i 3\n10\u{e9}\n"), vec![(2, 1)]);
+        assert_eq!(problems(".i 2\n10\u{e9}\n"), vec![(2, 3)]);
+        assert_eq!(problems(""), vec![(1, 1)]);
+    }
+}
@@ -0,0 +1,294 @@
+//! Random functions for tests and benchmarks
+//!
+//! The functions are generated with the SplitMix64 generator, so the
+//! same arguments and seed give the same CubeList on every platform.
+
+use super::cube::{Cube, MAX_NUM_VAR};
+use super::cubelist::CubeList;
+use super::rng::SplitMix64;
+
+/// The largest number of Cubes of a random function
+pub const MAX_CUBES: usize = 1 << 20;
+
+/// The largest number of variables times the number of Cubes of a random
+/// function, which bounds the memory it takes
+pub const MAX_LITERALS: usize = 1 << 26;
+
+/// This function returns an error message if a random function with
+/// num_var variables and num_cubes Cubes is larger than the caps, which
+/// are MAX_NUM_VAR, MAX_CUBES and MAX_LITERALS
+pub fn check_size(num_var: usize, num_cubes: usize) -> Result<(), String> {
+    if num_var > MAX_NUM_VAR {
+        Err(format!(
+            "Expected at most {} variables, found {}",
+            MAX_NUM_VAR, num_var
+        ))
+    } else if num_cubes > MAX_CUBES {
+        Err(format!(
+            "Expected at most {} cubes, found {}",
+            MAX_CUBES, num_cubes
+        ))
+    } else if num_var * num_cubes > MAX_LITERALS {
+        Err(format!(
+            "Expected at most {} variables times cubes, found {}",
+            MAX_LITERALS,
+            num_var * num_cubes
+        ))
+    } else {
+        Ok(())
+    }
+}
+
+/// RandomOptions shapes the functions generated by CubeList::random_with.
+/// The default options generate binate covers on all the variables
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct RandomOptions {
+    /// Give every literal of a variable the same polarity, so that the
+    /// cover is unate
+    pub unate: bool,
+    /// Use exactly this number of variables, chosen at random, instead
+    /// of all of them. It is capped at the number of variables
+    pub support: Option<usize>,
+    /// Make the cover a tautology
+    pub tautology: bool,
+}
+
+impl CubeList {
+    /// This function returns a random cover of num_var variables with
+    /// num_cubes Cubes, in which each variable of a Cube has a literal with
+    /// the probability literal_density. The cover only depends on the
+    /// arguments and on the seed
+    pub fn random(num_var: usize, num_cubes: usize, literal_density: f64, seed: u64) -> CubeList {
+        CubeList::random_with(
+            num_var,
+            num_cubes,
+            literal_density,
+            seed,
+            &RandomOptions::default(),
+        )
+    }
+
+    /// This function returns a random cover like random, shaped by the options
+    ///
+    /// Cubes drawn without literals, which would make the cover a tautology,
+    /// get the literal of a random variable instead, even when
+    /// literal_density is 0. So every Cube has a literal, except when the
+    /// support is empty, as with 0 variables, and the Cubes planted in a
+    /// tautology, described below. With the tautology
+    /// option, about half of the Cubes are the leaves of a random Shannon
+    /// expansion tree, which together cover every minterm, mixed with random
+    /// Cubes. As a unate cover is a tautology only if it has a Cube without
+    /// literals, a unate tautology has such a Cube instead of the tree. The
+    /// planted Cubes are not changed to make the support exact, so a
+    /// tautology needs at least 2 Cubes to use every variable of the support
+    ///
+    /// As an empty cover is not a tautology, a tautology has at least 1 Cube,
+    /// even when num_cubes is 0
+    pub fn random_with(
+        num_var: usize,
+        num_cubes: usize,
+        literal_density: f64,
+        seed: u64,
+        options: &RandomOptions,
+    ) -> CubeList {
+        let mut rng = SplitMix64::new(seed);
+        // The variables of the support, as indices from 0
+        let mut vars: Vec<usize> = (0..num_var).collect();
+        shuffle(&mut vars, &mut rng);
+        vars.truncate(options.support.unwrap_or(num_var).min(num_var));
+        vars.sort_unstable();
+        let polarity: Vec<i32> = (0..num_var)
+            .map(|_| if rng.below(2) == 0 { 1 } else { -1 })
+            .collect();
+        let literal = |rng: &mut SplitMix64, var: usize| {
+            if options.unate {
+                polarity[var]
+            } else if rng.below(2) == 0 {
+                1
+            } else {
+                -1
+            }
+        };
+
+        let mut cubes: Vec<Vec<i32>> = Vec::with_capacity(num_cubes);
+        if options.tautology && options.unate {
+            cubes.push(vec![0; num_var]);
+        } else if options.tautology {
+            cubes = shannon_leaves(num_var, &vars, (num_cubes / 2).max(1), &mut rng);
+        }
+        // The planted Cubes are kept as they are, as a literal added to
+        // one of them could uncover a minterm
+        let planted = cubes.len();
+        while cubes.len() < num_cubes {
+            let mut cube = vec![0; num_var];
+            for &var in &vars {
+                if chance(&mut rng, literal_density) {
+                    cube[var] = literal(&mut rng, var);
+                }
+            }
+            if !vars.is_empty() && cube.iter().all(|literal| *literal == 0) {
+                let var = vars[rng.below(vars.len())];
+                cube[var] = literal(&mut rng, var);
+            }
+            cubes.push(cube);
+        }
+        // Every variable of the support has a literal in some Cube
+        if cubes.len() > planted {
+            for &var in &vars {
+                if cubes.iter().all(|cube| cube[var] == 0) {
+                    let i = planted + rng.below(cubes.len() - planted);
+                    cubes[i][var] = literal(&mut rng, var);
+                }
+            }
+        }
+        shuffle(&mut cubes, &mut rng);
+
+        let mut cubelist = CubeList::new(num_var);
+        for cube in cubes {
+            cubelist.add_cube(Cube::from(cube));
+        }
+        cubelist
+    }
+}
+
+/// Returns true with the probability p
+fn chance(rng: &mut SplitMix64, p: f64) -> bool {
+    // The 53 bits of a f64 mantissa give a uniform number in [0, 1)
+    ((rng.next_u64() >> 11) as f64 / (1u64 << 53) as f64) < p
+}
+
+/// Shuffles the items in place with the Fisher-Yates algorithm
+fn shuffle<T>(items: &mut [T], rng: &mut SplitMix64) {
+    for i in (1..items.len()).rev() {
+        items.swap(i, rng.below(i + 1));
+    }
+}
+
+/// Returns the leaves of a random Shannon expansion tree on the variables,
+/// which cover every minterm once. Leaves are split on one of their free
+/// variables until there are num_leaves of them, or none can be split
+fn shannon_leaves(
+    num_var: usize,
+    vars: &[usize],
+    num_leaves: usize,
+    rng: &mut SplitMix64,
+) -> Vec<Vec<i32>> {
+    let mut leaves = vec![vec![0; num_var]];
+    while leaves.len() < num_leaves {
+        let splittable: Vec<usize> = (0..leaves.len())
+            .filter(|&i| vars.iter().any(|&var| leaves[i][var] == 0))
+            .collect();
+        if splittable.is_empty() {
+            break;
+        }
+        let leaf = splittable[rng.below(splittable.len())];
+        let free: Vec<usize> = vars
+            .iter()
+            .cloned()
+            .filter(|&var| leaves[leaf][var] == 0)
+            .collect();
+        let var = free[rng.below(free.len())];
+        let mut negative = leaves[leaf].clone();
+        negative[var] = -1;
+        leaves[leaf][var] = 1;
+        leaves.push(negative);
+    }
+    leaves
+}
+
+#[cfg(test)]
+#[allow(clippy::bool_assert_comparison)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn random() {
+        let cubelist = CubeList::random(10, 30, 0.3, 42);
+        assert_eq!((cubelist.num_var(), cubelist.len()), (10, 30));
+        assert_eq!(
+            cubelist.iter().collect::<Vec<_>>(),
+            CubeList::random(10, 30, 0.3, 42).iter().collect::<Vec<_>>()
+        );
+        assert_eq!(
+            cubelist.iter().collect::<Vec<_>>()
+                == CubeList::random(10, 30, 0.3, 43).iter().collect::<Vec<_>>(),
+            false
+        );
+        assert_eq!(cubelist.iter().all(|cube| cube.num_literals() > 0), true);
+        assert_eq!(CubeList::random(4, 0, 0.5, 1).is_empty(), true);
+        assert_eq!(CubeList::random(0, 3, 0.5, 1).len(), 3);
+        // Without a literal drawn, each Cube gets exactly one
+        let cubelist = CubeList::random(6, 20, 0.0, 5);
+        assert_eq!(cubelist.iter().all(|cube| cube.num_literals() == 1), true);
+        let empty_support = RandomOptions {
+            support: Some(0),
+            ..RandomOptions::default()
+        };
+        let cubelist = CubeList::random_with(6, 2, 0.5, 5, &empty_support);
+        assert_eq!(cubelist.num_literals(), 0);
+        // Every variable of the full support has a literal
+        assert_eq!(CubeList::random(8, 20, 1.0, 1).num_literals(), 160);
+    }
+
+    #[test]
+    fn check_size() {
+        assert_eq!(super::check_size(MAX_NUM_VAR, 1).is_ok(), true);
+        assert_eq!(super::check_size(MAX_NUM_VAR + 1, 1).is_err(), true);
+        assert_eq!(super::check_size(1, MAX_CUBES).is_ok(), true);
+        assert_eq!(super::check_size(1, MAX_CUBES + 1).is_err(), true);
+        assert_eq!(super::check_size(MAX_NUM_VAR, MAX_CUBES).is_err(), true);
+        assert_eq!(super::check_size(0, 0).is_ok(), true);
+    }
+
+    #[test]
+    fn random_options() {
+        let unate = RandomOptions {
+            unate: true,
+            ..RandomOptions::default()
+        };
+        let cubelist = CubeList::random_with(12, 40, 0.4, 7, &unate);
+        assert_eq!(cubelist.is_unate(), true);
+        assert_eq!(cubelist.support().len(), 12);
+
+        let support = RandomOptions {
+            support: Some(5),
+            ..RandomOptions::default()
+        };
+        for seed in 0..10 {
+            let cubelist = CubeList::random_with(12, 8, 0.1, seed, &support);
+            assert_eq!(cubelist.support().len(), 5);
+        }
+
+        for seed in 0..20 {
+            let tautology = RandomOptions {
+                tautology: true,
+                support: Some(8),
+                ..RandomOptions::default()
+            };
+            let cubelist = CubeList::random_with(10, 30, 0.3, seed, &tautology);
+            assert_eq!(cubelist.len(), 30);
+            assert_eq!(cubelist.is_tautology(), true);
+            assert_eq!(cubelist.iter().all(|cube| cube.num_literals() > 0), true);
+        }
+        let unate_tautology = RandomOptions {
+            unate: true,
+            tautology: true,
+            ..RandomOptions::default()
+        };
+        let cubelist = CubeList::random_with(6, 10, 0.5, 3, &unate_tautology);
+        assert_eq!(cubelist.is_tautology(), true);
+        assert_eq!(cubelist.binate_vars().is_empty(), true);
+
+        // A tautology has at least 1 Cube
+        for unate in [false, true] {
+            let options = RandomOptions {
+                unate,
+                tautology: true,
+                ..RandomOptions::default()
+            };
+            let cubelist = CubeList::random_with(5, 0, 0.5, 1, &options);
+            assert_eq!(cubelist.len(), 1);
+            assert_eq!(cubelist.is_tautology(), true);
+        }
+    }
+}
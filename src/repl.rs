@@ -0,0 +1,141 @@
+use rustyline::completion::Completer;
+use rustyline::error::ReadlineError;
+use rustyline::highlight::Highlighter;
+use rustyline::hint::Hinter;
+use rustyline::history::DefaultHistory;
+use rustyline::validate::Validator;
+use rustyline::{Context, Editor, Helper};
+use std::env;
+use std::path::PathBuf;
+use std::process;
+use urp::Session;
+
+/// The prompt printed before each command
+const PROMPT: &str = "urp> ";
+
+/// The prompt shown while reading the lines of a for or if block
+const BLOCK_PROMPT: &str = "...> ";
+
+/// ReplHelper completes command names and the numbers of stored functions
+struct ReplHelper {
+    functions: Vec<u32>,
+}
+
+impl Completer for ReplHelper {
+    type Candidate = String;
+
+    fn complete(
+        &self,
+        line: &str,
+        pos: usize,
+        _ctx: &Context<'_>,
+    ) -> rustyline::Result<(usize, Vec<String>)> {
+        let start = line[..pos]
+            .rfind(char::is_whitespace)
+            .map_or(0, |index| index + 1);
+        let word = &line[start..pos];
+        let candidates = if line[..start].trim().is_empty() {
+            Session::command_names()
+                .into_iter()
+                .filter(|name| name.starts_with(word))
+                .map(|name| name.to_string())
+                .collect()
+        } else {
+            self.functions
+                .iter()
+                .map(|function| function.to_string())
+                .filter(|function| function.starts_with(word))
+                .collect()
+        };
+        Ok((start, candidates))
+    }
+}
+
+impl Hinter for ReplHelper {
+    type Hint = String;
+}
+
+impl Highlighter for ReplHelper {}
+
+impl Validator for ReplHelper {}
+
+impl Helper for ReplHelper {}
+
+/// Returns the file that keeps the history between sessions
+fn history_file() -> Option<PathBuf> {
+    env::var_os("HOME").map(|home| PathBuf::from(home).join(".urp_history"))
+}
+
+/// Runs the commands typed at the terminal with line editing, history and
+/// tab completion, until the quit command or the end of input
+///
+/// Ctrl-C while a command runs cancels its complement or tautology check.
+/// A second Ctrl-C before the command stops exits, for the commands that
+/// cannot be cancelled
+pub fn run(mut session: Session) {
+    let cancel = session.cancel_token();
+    let handler = ctrlc::set_handler(move || {
+        if cancel.is_cancelled() {
+            process::exit(130);
+        }
+        cancel.cancel();
+    });
+    if let Err(error) = handler {
+        eprintln!("{}", error);
+    }
+
+    let mut editor: Editor<ReplHelper, DefaultHistory> = match Editor::new() {
+        Ok(editor) => editor,
+        Err(error) => {
+            eprintln!("{}", error);
+            return;
+        }
+    };
+    editor.set_helper(Some(ReplHelper { functions: vec![] }));
+    let history = history_file();
+    if let Some(history) = &history {
+        // The history file does not exist before the first session
+        let _ = editor.load_history(history);
+    }
+
+    let mut line_num = 0;
+    loop {
+        let prompt = if session.in_block() {
+            BLOCK_PROMPT
+        } else {
+            PROMPT
+        };
+        let line = match editor.readline(prompt) {
+            Ok(line) => line,
+            Err(ReadlineError::Interrupted) => continue,
+            Err(ReadlineError::Eof) => break,
+            Err(error) => {
+                eprintln!("{}", error);
+                break;
+            }
+        };
+        line_num += 1;
+        if !line.trim().is_empty() {
+            let _ = editor.add_history_entry(line.as_str());
+        }
+        // A Ctrl-C that arrived after the last command finished cancels nothing
+        session.cancel_token().reset();
+        match session.handle_command(&line, "<stdin>", line_num) {
+            Ok(true) => {}
+            Ok(false) => break,
+            Err(error) => eprintln!("{}", error),
+        }
+        if let Some(helper) = editor.helper_mut() {
+            helper.functions = session.function_ids();
+        }
+    }
+
+    if let Err(error) = session.finish() {
+        eprintln!("{}", error);
+    }
+    if let Some(history) = &history {
+        if let Err(error) = editor.save_history(history) {
+            eprintln!("{}: {}", history.display(), error);
+        }
+    }
+}
@@ -0,0 +1,46 @@
+//! A small seeded random number generator
+//!
+//! Randomized heuristics and generators must give the same results for
+//! the same seed on every platform and in every run, so they use this
+//! SplitMix64 generator instead of an external crate.
+
+/// SplitMix64 is a fast 64 bit generator with a single u64 of state
+#[derive(Debug, Clone)]
+pub(crate) struct SplitMix64(u64);
+
+impl SplitMix64 {
+    /// Returns a new generator started from the seed
+    pub(crate) fn new(seed: u64) -> Self {
+        SplitMix64(seed)
+    }
+
+    /// Returns the next random number
+    pub(crate) fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /// Returns a random number below bound, which must not be 0
+    pub(crate) fn below(&mut self, bound: usize) -> usize {
+        (self.next_u64() % bound as u64) as usize
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::bool_assert_comparison)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn split_mix_64() {
+        // The first outputs for the seed 0 given by the reference implementation
+        let mut rng = SplitMix64::new(0);
+        assert_eq!(rng.next_u64(), 0xe220_a839_7b1d_cdaf);
+        assert_eq!(rng.next_u64(), 0x6e78_9e6a_a1b9_65f4);
+        let mut rng = SplitMix64::new(7);
+        assert_eq!((0..100).all(|_| rng.below(3) < 3), true);
+    }
+}
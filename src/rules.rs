@@ -0,0 +1,104 @@
+//! Counters of the rules that end the tautology recursion
+//!
+//! Before splitting a cover on a variable, is_tautology tries a number of
+//! rules that decide the result at once or replace the cover by a smaller
+//! one. RuleCounters counts how many times each rule was applied, to
+//! measure how useful each of them is.
+
+use std::fmt;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// Rule is one of the rules applied by is_tautology before splitting a cover
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Rule {
+    /// The cover has a Cube without literals, so it is a tautology
+    DontCareRow,
+    /// The cover has the Cubes x and x' for a variable x, so it is a tautology
+    SingleVariable,
+    /// The Cubes cover less than 2^N minterms, so it is not a tautology
+    MintermCount,
+    /// The cover is unate without a Cube free of literals, so it is not a tautology
+    Unate,
+    /// The Cubes with literals of unate variables are removed from the cover
+    UnateReduction,
+    /// The cover is split into covers on disjoint variables, one of which
+    /// must be a tautology
+    Components,
+    /// The cover is split into its two cofactors, both of which must be
+    /// tautologies
+    Split,
+}
+
+impl Rule {
+    /// Every rule, in the order in which they are tried
+    pub const ALL: [Rule; 7] = [
+        Rule::DontCareRow,
+        Rule::SingleVariable,
+        Rule::MintermCount,
+        Rule::Unate,
+        Rule::UnateReduction,
+        Rule::Components,
+        Rule::Split,
+    ];
+
+    /// Returns the name of the rule
+    pub fn name(self) -> &'static str {
+        match self {
+            Rule::DontCareRow => "don't care row",
+            Rule::SingleVariable => "single variable",
+            Rule::MintermCount => "minterm count",
+            Rule::Unate => "unate",
+            Rule::UnateReduction => "unate reduction",
+            Rule::Components => "components",
+            Rule::Split => "split",
+        }
+    }
+
+    /// Returns the position of the rule in ALL
+    fn index(self) -> usize {
+        Rule::ALL.iter().position(|rule| *rule == self).unwrap()
+    }
+}
+
+impl fmt::Display for Rule {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+/// RuleCounters holds the number of times each Rule was applied.
+/// It can be shared by the tasks of the parallel recursion
+#[derive(Debug, Default)]
+pub struct RuleCounters([AtomicUsize; 7]);
+
+impl RuleCounters {
+    /// Returns new counters, all 0
+    pub fn new() -> Self {
+        RuleCounters::default()
+    }
+
+    /// Adds 1 to the counter of the rule
+    pub fn count(&self, rule: Rule) {
+        self.0[rule.index()].fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Returns the number of times the rule was applied
+    pub fn get(&self, rule: Rule) -> usize {
+        self.0[rule.index()].load(Ordering::Relaxed)
+    }
+
+    /// Returns every rule with the number of times it was applied
+    pub fn counts(&self) -> Vec<(Rule, usize)> {
+        Rule::ALL
+            .iter()
+            .map(|rule| (*rule, self.get(*rule)))
+            .collect()
+    }
+
+    /// Sets every counter to 0
+    pub fn clear(&self) {
+        for counter in &self.0 {
+            counter.store(0, Ordering::Relaxed);
+        }
+    }
+}
@@ -0,0 +1,1161 @@
+use super::cache::{CacheStats, ComputedTable};
+use super::cube::var_name;
+use super::cubelist::CubeList;
+use super::error::{Limit, UrpError};
+use super::options::{CancelToken, Limits, SplitStrategy, UrpOptions};
+use super::random::{self, RandomOptions};
+use super::rules::RuleCounters;
+use super::trace::Tracer;
+use std::collections::HashMap;
+use std::io::{self, Write};
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::time::Duration;
+
+mod script;
+
+/// The name, usage and description of every command
+///
+/// Commands with a single letter name are recognised by their first
+/// letter only, and the others by their full name
+const COMMANDS: &[(&str, &str, &str)] = &[
+    ("r", "r <f>", "Reads the function f from f.pcn"),
+    ("p", "p <f>", "Writes the function f to f.pcn"),
+    ("rjson", "rjson <f>", "Reads the function f from f.json"),
+    ("pjson", "pjson <f>", "Writes the function f to f.json"),
+    ("+", "+ <out> <f> <g>", "Stores f OR g as out"),
+    ("&", "& <out> <f> <g>", "Stores f AND g as out"),
+    ("!", "! <out> <f>", "Stores NOT f as out"),
+    (
+        "s",
+        "s <out> <f> <var> <g>",
+        "Stores f with g substituted for the variable var as out",
+    ),
+    (
+        "m",
+        "m <out> <f> <v1> .. <vn>",
+        "Stores f with each variable i renamed to vi as out",
+    ),
+    (
+        "i",
+        "i <out> <f>",
+        "Stores an irredundant cover of f as out",
+    ),
+    (
+        "a",
+        "a <out> <f>",
+        "Stores all the prime implicants of f as out and writes them to out.pcn",
+    ),
+    ("e", "e <out> <f>", "Stores a minimum cover of f as out"),
+    (
+        "gen",
+        "gen <out> <vars> <cubes> <density> <seed> [unate] [support <n>] [taut]",
+        "Stores a random function as out, with the probability density of a literal for \
+         each variable of a cube, optionally unate, on n variables or a tautology",
+    ),
+    ("t", "t <f>", "Prints whether f is a tautology"),
+    ("show", "show <f>", "Prints f as a sum of products"),
+    (
+        "stats",
+        "stats [f]",
+        "Prints the number of cubes, literals and minterms and the variables of f, \
+         or the hits and misses of the cache and how often each tautology rule was applied",
+    ),
+    (
+        "diff",
+        "diff <f> <g>",
+        "Prints the cubes of f that are not in g with <, and of g not in f with >",
+    ),
+    (
+        "list",
+        "list",
+        "Lists the stored functions with their number of variables and cubes",
+    ),
+    (
+        "help",
+        "help [command]",
+        "Lists the commands, or describes a single command",
+    ),
+    (
+        "for",
+        "for <var> in <values> .. end",
+        "Runs the lines up to end once for each value, which can be a range like 1..5, as $var",
+    ),
+    (
+        "if",
+        "if [not] taut <f> | equiv <f> <g> then .. [else ..] end",
+        "Runs the lines up to else or end if the condition holds, or else the lines after else",
+    ),
+    (
+        "assert",
+        "assert [not] taut <f> | equiv <f> <g>",
+        "Stops the script with an error if the condition does not hold",
+    ),
+    ("echo", "echo <text>", "Prints the text"),
+    (
+        "let",
+        "let <var> <value>",
+        "Sets the variable var, used as $var",
+    ),
+    ("include", "include <file>", "Runs the commands in the file"),
+    (
+        "set",
+        "set <option> <value>",
+        "Sets an option of the complements and tautology checks: parallel on|off \
+         (off by default), cache on|off (on by default), trace on|off (off by default), \
+         split binate|binate-balanced|balanced|first|literals|random [seed], and the limits depth, \
+         cubes and timeout (in milliseconds) to a number or off",
+    ),
+    (
+        "trace",
+        "trace <file>",
+        "Writes the recursion tree recorded since set trace on to file, as JSON if its \
+         name ends in .json or else as folded stacks for flame graphs",
+    ),
+    ("q", "q", "Quits the program"),
+];
+
+/// Session is the command interpreter. It stores the boolean
+/// functions by their number and runs commands on them
+///
+/// The messages printed by the commands are written to the output
+/// of the session, which is the standard output by default. The files
+/// read and written by the commands are in the directory of the session,
+/// which is the current directory by default
+pub struct Session {
+    index: HashMap<u32, CubeList>,
+    output: Box<dyn Write>,
+    dir: PathBuf,
+    vars: HashMap<String, String>,
+    block: Vec<(usize, String)>,
+    block_source: String,
+    include_depth: usize,
+    parallel: bool,
+    cache: Option<ComputedTable>,
+    rules: RuleCounters,
+    split: SplitStrategy,
+    limits: Limits,
+    cancel: CancelToken,
+    trace: Option<Tracer>,
+    failed_checks: usize,
+}
+
+impl Default for Session {
+    fn default() -> Self {
+        Session::new()
+    }
+}
+
+impl Session {
+    /// Returns a new Session without any stored function
+    pub fn new() -> Self {
+        Session::with_output(Box::new(io::stdout()))
+    }
+
+    /// Returns a new Session that writes its messages to output
+    pub fn with_output(output: Box<dyn Write>) -> Self {
+        Session {
+            index: HashMap::new(),
+            output,
+            dir: PathBuf::new(),
+            vars: HashMap::new(),
+            block: vec![],
+            block_source: String::new(),
+            include_depth: 0,
+            parallel: false,
+            cache: Some(ComputedTable::new()),
+            rules: RuleCounters::new(),
+            split: SplitStrategy::default(),
+            limits: Limits::default(),
+            cancel: CancelToken::new(),
+            trace: None,
+            failed_checks: 0,
+        }
+    }
+
+    /// Returns the number of t commands that found their function not to
+    /// be a tautology
+    pub fn failed_checks(&self) -> usize {
+        self.failed_checks
+    }
+
+    /// Sets the directory of the files read and written by the commands
+    pub fn set_dir<P: Into<PathBuf>>(&mut self, dir: P) {
+        self.dir = dir.into();
+    }
+
+    /// Sets whether the results of complements and tautology checks of the
+    /// sub-covers met during the recursion are kept in a cache, which is
+    /// the case by default. Turning the cache off discards its results
+    pub fn set_cache(&mut self, cache: bool) {
+        self.cache = if cache {
+            self.cache.take().or_else(|| Some(ComputedTable::new()))
+        } else {
+            None
+        };
+    }
+
+    /// Sets the strategy that chooses the variable on which complements
+    /// and tautology checks split the covers
+    pub fn set_split(&mut self, split: SplitStrategy) {
+        self.split = split;
+    }
+
+    /// Sets the limits of each complement and tautology check
+    pub fn set_limits(&mut self, limits: Limits) {
+        self.limits = limits;
+    }
+
+    /// Returns a token that cancels the complement or tautology check that
+    /// is running, e.g. from another thread. The command then fails with a
+    /// LimitExceeded error, and the following commands run normally
+    pub fn cancel_token(&self) -> CancelToken {
+        self.cancel.clone()
+    }
+
+    /// Sets whether the recursion of complements and tautology checks is
+    /// recorded, which is not the case by default. Turning the tracing
+    /// off discards the recorded nodes
+    pub fn set_trace(&mut self, trace: bool) {
+        self.trace = if trace {
+            self.trace.take().or_else(|| Some(Tracer::new()))
+        } else {
+            None
+        };
+    }
+
+    /// Returns the tracer that records the recursion, if it is traced
+    pub fn tracer(&self) -> Option<&Tracer> {
+        self.trace.as_ref()
+    }
+
+    /// Sets whether complements and tautology checks split their
+    /// recursion into parallel tasks
+    pub fn set_parallel(&mut self, parallel: bool) {
+        self.parallel = parallel;
+    }
+
+    /// Returns the path of the file that stores the given function
+    fn file_path(&self, function: u32, extension: &str) -> String {
+        self.dir
+            .join(format!("{}.{}", function, extension))
+            .to_string_lossy()
+            .into_owned()
+    }
+
+    /// Returns the boolean function stored with the given number
+    pub fn get(&self, function: u32) -> Option<&CubeList> {
+        self.index.get(&function)
+    }
+
+    /// Returns the numbers of the stored boolean functions in increasing order
+    pub fn function_ids(&self) -> Vec<u32> {
+        let mut functions: Vec<u32> = self.index.keys().cloned().collect();
+        functions.sort_unstable();
+        functions
+    }
+
+    /// Returns the names of all the commands
+    pub fn command_names() -> Vec<&'static str> {
+        COMMANDS.iter().map(|(name, _, _)| *name).collect()
+    }
+
+    /// Stores a boolean function with the given number, replacing
+    /// any function already stored with that number
+    pub fn insert(&mut self, function: u32, cubelist: CubeList) {
+        self.index.insert(function, cubelist);
+    }
+
+    /// Returns the boolean function stored with the given number
+    /// or an error if it is not present
+    fn get_function(&self, function: u32) -> Result<&CubeList, UrpError> {
+        self.index
+            .get(&function)
+            .ok_or(UrpError::UnknownFunction(function))
+    }
+
+    /// Returns the options of the complements and tautology checks
+    fn options(&self) -> UrpOptions<'_> {
+        UrpOptions {
+            parallel: self.parallel,
+            cache: self.cache.as_ref(),
+            rules: Some(&self.rules),
+            split: self.split,
+            limits: self.limits,
+            cancel: Some(&self.cancel),
+            trace: self.trace.as_ref(),
+        }
+    }
+
+    /// Returns if the function stored with the given number is a tautology
+    fn is_tautology(&self, function: u32) -> Result<bool, UrpError> {
+        let cubelist = self.get_function(function)?;
+        self.reset_cancel(cubelist.is_tautology_with(&self.options()))
+    }
+
+    /// Returns if the functions stored with the given numbers are equivalent
+    fn is_equivalent(&self, function_1: u32, function_2: u32) -> Result<bool, UrpError> {
+        let cubelist_1 = self.get_function(function_1)?;
+        let cubelist_2 = self.get_function(function_2)?;
+        self.reset_cancel(cubelist_1.is_equivalent_with(cubelist_2, &self.options()))
+    }
+
+    /// Returns the complement of the function stored with the given number
+    fn complement(&self, function: u32) -> Result<CubeList, UrpError> {
+        let cubelist = self.get_function(function)?;
+        self.reset_cancel(cubelist.complement_with(&self.options()))
+    }
+
+    /// Makes the cancel token usable again if it cancelled the operation
+    /// that returned the result
+    fn reset_cancel<T>(&self, result: Result<T, UrpError>) -> Result<T, UrpError> {
+        if let Err(UrpError::LimitExceeded(Limit::Cancelled)) = result {
+            self.cancel.reset();
+        }
+        result
+    }
+
+    /// Writes a message to the output of the session
+    fn print(&mut self, message: String) -> Result<(), UrpError> {
+        writeln!(self.output, "{}", message).map_err(|error| UrpError::Io {
+            path: "<output>".to_string(),
+            error,
+        })
+    }
+
+    /// This function handles a single line of a script
+    /// It returns false if the command is a quit command, else it returns true
+    ///
+    /// Empty lines and comments, which start with #, are ignored. The lines
+    /// of a for or if block are kept until the end of the block, and then
+    /// the whole block is run. The source and line_num are used to report
+    /// where a command that could not be parsed was read from
+    pub fn handle_command(
+        &mut self,
+        line: &str,
+        source: &str,
+        line_num: usize,
+    ) -> Result<bool, UrpError> {
+        let line = script::strip_comment(line);
+        if self.block.is_empty() && !script::starts_block(line) {
+            return self.run_statement(line, source, line_num);
+        }
+        if self.block.is_empty() {
+            self.block_source = source.to_string();
+        }
+        self.block.push((line_num, line.to_string()));
+        if script::open_blocks(&self.block) > 0 {
+            return Ok(true);
+        }
+        let block = std::mem::take(&mut self.block);
+        self.run_lines(&block, source)
+    }
+
+    /// Returns true if the lines of a for or if block are being read
+    pub fn in_block(&self) -> bool {
+        !self.block.is_empty()
+    }
+
+    /// Ends the script, returning an error if a block has no end
+    pub fn finish(&mut self) -> Result<(), UrpError> {
+        let block = std::mem::take(&mut self.block);
+        match block.first() {
+            Some((line_num, line)) => Err(script::unterminated_block(
+                &self.block_source,
+                *line_num,
+                line,
+            )),
+            None => Ok(()),
+        }
+    }
+
+    /// Runs a single command
+    /// It returns false if the command is a quit command, else it returns true
+    fn run_command(&mut self, command: &Command) -> Result<bool, UrpError> {
+        let (column, name) = command.words[0];
+        // Single letter commands are recognised by their first letter only
+        let key = if name.len() > 1 && Session::command_names().contains(&name) {
+            name
+        } else {
+            &name[..name.chars().next().unwrap().len_utf8()]
+        };
+        match key {
+            "q" => return Ok(false),
+            "p" => {
+                // Prints the function into a file
+                command.expect_args(1)?;
+                let function: u32 = command.arg(1)?;
+                self.get_function(function)?
+                    .write_to_file(&self.file_path(function, "pcn"))?;
+            }
+            "t" => {
+                // Checks if a function is a tautology
+                command.expect_args(1)?;
+                let function: u32 = command.arg(1)?;
+                if self.is_tautology(function)? {
+                    self.print(format!("{} is a tautology", function))?;
+                } else {
+                    self.failed_checks += 1;
+                    self.print(format!("{} is not a tautology", function))?;
+                }
+            }
+            "r" => {
+                // Reads the function from a file
+                command.expect_args(1)?;
+                let function: u32 = command.arg(1)?;
+                self.index.insert(
+                    function,
+                    CubeList::read_from_file(&self.file_path(function, "pcn"))?,
+                );
+            }
+            "!" => {
+                // Perform Complement of a function
+                command.expect_args(2)?;
+                let output: u32 = command.arg(1)?;
+                let input: u32 = command.arg(2)?;
+                let cubelist = self.complement(input)?;
+                self.index.insert(output, cubelist);
+            }
+            "&" => {
+                // Perform AND of two functions
+                command.expect_args(3)?;
+                let output: u32 = command.arg(1)?;
+                let input1: u32 = command.arg(2)?;
+                let input2: u32 = command.arg(3)?;
+                let cubelist = self.get_function(input1)?.and(self.get_function(input2)?);
+                self.index.insert(output, cubelist);
+            }
+            "+" => {
+                // Perform OR of two functions
+                command.expect_args(3)?;
+                let output: u32 = command.arg(1)?;
+                let input1: u32 = command.arg(2)?;
+                let input2: u32 = command.arg(3)?;
+                let cubelist = self.get_function(input1)?.or(self.get_function(input2)?);
+                self.index.insert(output, cubelist);
+            }
+            "s" => {
+                // Substitute a function into a variable of another function
+                command.expect_args(4)?;
+                let output: u32 = command.arg(1)?;
+                let input1: u32 = command.arg(2)?;
+                let var_num: usize = command.arg(3)?;
+                let input2: u32 = command.arg(4)?;
+                let cubelist = self
+                    .get_function(input1)?
+                    .compose(var_num, self.get_function(input2)?)?;
+                self.index.insert(output, cubelist);
+            }
+            "a" => {
+                // Generate all the prime implicants of a function and write them to a file
+                command.expect_args(2)?;
+                let output: u32 = command.arg(1)?;
+                let input: u32 = command.arg(2)?;
+                let primes = self.get_function(input)?.primes();
+                primes.write_to_file(&self.file_path(output, "pcn"))?;
+                self.index.insert(output, primes);
+            }
+            "e" => {
+                // Perform exact minimization of a function
+                command.expect_args(2)?;
+                let output: u32 = command.arg(1)?;
+                let input: u32 = command.arg(2)?;
+                let (cubelist, optimal) = self.get_function(input)?.minimize_exact();
+                if optimal {
+                    self.print(format!(
+                        "{} is a minimum cover with {} cubes",
+                        output,
+                        cubelist.len()
+                    ))?;
+                } else {
+                    self.print(format!(
+                        "{} is a cover with {} cubes, not proven to be a minimum",
+                        output,
+                        cubelist.len()
+                    ))?;
+                }
+                self.index.insert(output, cubelist);
+            }
+            "i" => {
+                // Remove the redundant cubes of a function
+                command.expect_args(2)?;
+                let output: u32 = command.arg(1)?;
+                let input: u32 = command.arg(2)?;
+                let cubelist = self.get_function(input)?.make_irredundant();
+                self.index.insert(output, cubelist);
+            }
+            #[cfg(feature = "serde")]
+            "rjson" => {
+                // Reads the function from a JSON file
+                command.expect_args(1)?;
+                let function: u32 = command.arg(1)?;
+                let cubelist = CubeList::read_json(&self.file_path(function, "json"))?;
+                self.index.insert(function, cubelist);
+            }
+            #[cfg(feature = "serde")]
+            "pjson" => {
+                // Prints the function into a JSON file
+                command.expect_args(1)?;
+                let function: u32 = command.arg(1)?;
+                let cubelist = self.get_function(function)?;
+                cubelist.write_json(&self.file_path(function, "json"))?;
+            }
+            #[cfg(not(feature = "serde"))]
+            "rjson" | "pjson" => {
+                return Err(command.error(
+                    column,
+                    "JSON support requires the serde feature".to_string(),
+                ));
+            }
+            "show" => {
+                // Prints a function as a sum of products
+                command.expect_args(1)?;
+                let function: u32 = command.arg(1)?;
+                let message = self.get_function(function)?.to_sop_string();
+                self.print(message)?;
+            }
+            "stats" if command.words.len() == 1 => {
+                // Prints the statistics of the cache and the tautology rules
+                let mut message = match &self.cache {
+                    Some(cache) => format!(
+                        "tautology cache: {}\ncomplement cache: {}",
+                        cache_stats(cache.tautology_stats()),
+                        cache_stats(cache.complement_stats())
+                    ),
+                    None => "cache: off".to_string(),
+                };
+                for (rule, count) in self.rules.counts() {
+                    message.push_str(&format!("\nrule {}: {}", rule, count));
+                }
+                self.print(message)?;
+            }
+            "stats" => {
+                // Prints the statistics of a function
+                command.expect_args(1)?;
+                let function: u32 = command.arg(1)?;
+                let cubelist = self.get_function(function)?;
+                let num_var = cubelist.num_var();
+                let support = cubelist.support();
+                let binate = cubelist.binate_vars();
+                let names = |vars: Vec<usize>| {
+                    if vars.is_empty() {
+                        return "none".to_string();
+                    }
+                    vars.into_iter()
+                        .map(|var_num| var_name(var_num, num_var))
+                        .collect::<Vec<String>>()
+                        .join(" ")
+                };
+                let message = format!(
+                    "cubes: {}\nliterals: {}\nsupport: {} of {} variables\n\
+                     unate: {}\nbinate: {}\nminterms: {}",
+                    cubelist.len(),
+                    cubelist.num_literals(),
+                    support.len(),
+                    num_var,
+                    names(
+                        support
+                            .into_iter()
+                            .filter(|var_num| !binate.contains(var_num))
+                            .collect()
+                    ),
+                    names(binate),
+                    cubelist.minterm_count()
+                );
+                self.print(message)?;
+            }
+            "diff" => {
+                // Prints the cubes present in only one of two functions
+                command.expect_args(2)?;
+                let input1: u32 = command.arg(1)?;
+                let input2: u32 = command.arg(2)?;
+                let (cubelist_1, cubelist_2) =
+                    (self.get_function(input1)?, self.get_function(input2)?);
+                let num_var = cubelist_1.num_var().max(cubelist_2.num_var());
+                let (cubelist_1, cubelist_2) = (
+                    cubelist_1.extend_vars(num_var),
+                    cubelist_2.extend_vars(num_var),
+                );
+                for cube in &cubelist_1 {
+                    if !cubelist_2.contains_cube(cube) {
+                        self.print(format!("< {}", cube.to_sop_string()))?;
+                    }
+                }
+                for cube in &cubelist_2 {
+                    if !cubelist_1.contains_cube(cube) {
+                        self.print(format!("> {}", cube.to_sop_string()))?;
+                    }
+                }
+            }
+            "list" => {
+                // Lists the stored functions
+                for function in self.function_ids() {
+                    let cubelist = &self.index[&function];
+                    let message = format!(
+                        "{}: {} variables, {} cubes",
+                        function,
+                        cubelist.num_var(),
+                        cubelist.len()
+                    );
+                    self.print(message)?;
+                }
+            }
+            "help" => {
+                // Describes the commands
+                match command.words.get(1) {
+                    Some((column, name)) => {
+                        let (_, usage, description) = COMMANDS
+                            .iter()
+                            .find(|(command_name, _, _)| command_name == name)
+                            .ok_or_else(|| {
+                                command.error(*column, format!("Invalid command {}", name))
+                            })?;
+                        self.print(format!("{}\n    {}", usage, description))?;
+                    }
+                    None => {
+                        for (_, usage, description) in COMMANDS {
+                            self.print(format!("{:<25} {}", usage, description))?;
+                        }
+                    }
+                }
+            }
+            "set" => {
+                // Changes an option of the session
+                command.expect_args(2)?;
+                let (column, option) = command.words[1];
+                match option {
+                    "parallel" => self.parallel = command.switch(2)?,
+                    "cache" => self.set_cache(command.switch(2)?),
+                    "trace" => self.set_trace(command.switch(2)?),
+                    "depth" => self.limits.max_depth = command.limit(2)?,
+                    "cubes" => self.limits.max_cubes = command.limit(2)?,
+                    "timeout" => {
+                        let timeout: Option<u64> = command.limit(2)?;
+                        self.limits.timeout = timeout.map(Duration::from_millis);
+                    }
+                    "split" => {
+                        // The seed of the random strategy can follow its name
+                        let (column, name) = command.words[2];
+                        self.split = match command.words.get(3) {
+                            Some(_) if name == "random" => SplitStrategy::Random {
+                                seed: command.arg(3)?,
+                            },
+                            _ => name
+                                .parse()
+                                .map_err(|message| command.error(column, message))?,
+                        };
+                    }
+                    _ => return Err(command.error(column, format!("Unknown option {}", option))),
+                }
+            }
+            "gen" => {
+                // Generates a random function
+                command.expect_args(5)?;
+                let output: u32 = command.arg(1)?;
+                let num_var: usize = command.arg(2)?;
+                let num_cubes: usize = command.arg(3)?;
+                random::check_size(num_var, num_cubes)
+                    .map_err(|message| command.error(command.words[2].0, message))?;
+                let density: f64 = command.arg(4)?;
+                let seed: u64 = command.arg(5)?;
+                let options = command.random_options(6)?;
+                let cubelist = CubeList::random_with(num_var, num_cubes, density, seed, &options);
+                self.index.insert(output, cubelist);
+            }
+            "trace" => {
+                // Writes the recorded recursion tree
+                command.expect_args(1)?;
+                let (column, file) = command.words[1];
+                let tracer = self.trace.as_ref().ok_or_else(|| {
+                    command.error(column, "Tracing is off, see set trace on".to_string())
+                })?;
+                let path = self.dir.join(file).to_string_lossy().into_owned();
+                if file.ends_with(".json") {
+                    tracer.write_json(&path)?;
+                } else {
+                    tracer.write_folded(&path)?;
+                }
+            }
+            "m" => {
+                // Rename the variables of a function
+                command.expect_args(2)?;
+                let output: u32 = command.arg(1)?;
+                let input: u32 = command.arg(2)?;
+                let permutation = (3..command.words.len())
+                    .map(|i| command.arg::<usize>(i))
+                    .collect::<Result<Vec<usize>, UrpError>>()?;
+                let cubelist = self.get_function(input)?.permute(&permutation)?;
+                self.index.insert(output, cubelist);
+            }
+            _ => {
+                return Err(command.error(column, format!("Invalid command {}", name)));
+            }
+        }
+        Ok(true)
+    }
+}
+
+/// Returns the statistics of a table of the cache as a message
+fn cache_stats(stats: CacheStats) -> String {
+    format!(
+        "{} hits, {} misses, {} entries",
+        stats.hits, stats.misses, stats.entries
+    )
+}
+
+/// Command holds the words of a single command along with
+/// the location from where it was read
+struct Command<'a> {
+    source: &'a str,
+    line: usize,
+    words: Vec<(usize, &'a str)>,
+}
+
+impl<'a> Command<'a> {
+    /// Returns a parse error at the given column of the command
+    fn error(&self, column: usize, message: String) -> UrpError {
+        UrpError::Parse {
+            source: self.source.to_string(),
+            line: self.line,
+            column,
+            message,
+        }
+    }
+
+    /// Returns an error if the command has less than num_args arguments
+    fn expect_args(&self, num_args: usize) -> Result<(), UrpError> {
+        if self.words.len() <= num_args {
+            let (column, word) = self.words[self.words.len() - 1];
+            let message = if num_args == 1 {
+                "Expected 1 argument".to_string()
+            } else {
+                format!("Expected {} arguments", num_args)
+            };
+            return Err(self.error(column + word.len(), message));
+        }
+        Ok(())
+    }
+
+    /// Parses the argument at position i of the command as on or off
+    fn switch(&self, i: usize) -> Result<bool, UrpError> {
+        match self.words[i] {
+            (_, "on") => Ok(true),
+            (_, "off") => Ok(false),
+            (column, word) => {
+                Err(self.error(column, format!("Expected on or off, found {}", word)))
+            }
+        }
+    }
+
+    /// Parses the argument at position i of the command as a number,
+    /// or as None if it is off
+    fn limit<T: FromStr>(&self, i: usize) -> Result<Option<T>, UrpError> {
+        match self.words[i] {
+            (_, "off") => Ok(None),
+            _ => self.arg(i).map(Some),
+        }
+    }
+
+    /// Parses the options of the gen command that start at position i
+    fn random_options(&self, i: usize) -> Result<RandomOptions, UrpError> {
+        let mut options = RandomOptions::default();
+        let mut i = i;
+        while i < self.words.len() {
+            match self.words[i] {
+                (_, "unate") => options.unate = true,
+                (_, "taut") => options.tautology = true,
+                (_, "support") => {
+                    self.expect_args(i + 1)?;
+                    options.support = Some(self.arg(i + 1)?);
+                    i += 1;
+                }
+                (column, word) => {
+                    return Err(self.error(column, format!("Unknown option {}", word)))
+                }
+            }
+            i += 1;
+        }
+        Ok(options)
+    }
+
+    /// Parses the argument at position i of the command as a number
+    fn arg<T: FromStr>(&self, i: usize) -> Result<T, UrpError> {
+        let (column, word) = self.words[i];
+        word.parse::<T>()
+            .map_err(|_| self.error(column, format!("Expected a number, found {}", word)))
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::bool_assert_comparison)]
+mod test {
+    use super::*;
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    /// Output that can still be read after it is given to a Session
+    #[derive(Clone, Default)]
+    struct SharedOutput(Rc<RefCell<Vec<u8>>>);
+
+    impl Write for SharedOutput {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.borrow_mut().write(buf)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    impl SharedOutput {
+        /// Returns the text written so far and clears it
+        fn take(&self) -> String {
+            String::from_utf8(self.0.borrow_mut().split_off(0)).unwrap()
+        }
+    }
+
+    #[test]
+    fn handle_command() {
+        let mut session = Session::with_output(Box::new(io::sink()));
+        session.insert(1, CubeList::from(vec![vec![1, 0]]));
+        session.insert(2, CubeList::from(vec![vec![-1, 0]]));
+        assert_eq!(session.handle_command("+ 3 1 2", "test", 1).unwrap(), true);
+        assert_eq!(session.get(3).unwrap().is_tautology(), true);
+        assert_eq!(
+            session
+                .handle_command("set parallel on", "test", 1)
+                .unwrap(),
+            true
+        );
+        assert_eq!(session.handle_command("! 4 3", "test", 1).unwrap(), true);
+        assert_eq!(session.get(4).unwrap().is_empty(), true);
+        // Only the t commands that find no tautology are failed checks
+        session.handle_command("t 3", "test", 1).unwrap();
+        assert_eq!(session.failed_checks(), 0);
+        session.handle_command("t 1", "test", 1).unwrap();
+        session.handle_command("t 4", "test", 1).unwrap();
+        assert_eq!(session.failed_checks(), 2);
+        assert_eq!(session.handle_command("", "test", 2).unwrap(), true);
+        assert_eq!(session.handle_command("q", "test", 3).unwrap(), false);
+    }
+
+    #[test]
+    fn list_and_help() {
+        let output = SharedOutput::default();
+        let mut session = Session::with_output(Box::new(output.clone()));
+        session.insert(7, CubeList::from(vec![vec![1, 0]]));
+        session.insert(2, CubeList::from(vec![vec![-1, 0, 0], vec![0, 1, 0]]));
+        assert_eq!(session.function_ids(), vec![2, 7]);
+        assert_eq!(session.handle_command("list", "test", 1).unwrap(), true);
+        assert_eq!(
+            output.take(),
+            "2: 3 variables, 2 cubes\n7: 2 variables, 1 cubes\n"
+        );
+        assert_eq!(session.handle_command("help", "test", 2).unwrap(), true);
+        assert_eq!(output.take().lines().count(), COMMANDS.len());
+        assert_eq!(session.handle_command("help +", "test", 3).unwrap(), true);
+        assert_eq!(output.take(), "+ <out> <f> <g>\n    Stores f OR g as out\n");
+        assert_eq!(session.handle_command("help x", "test", 4).is_err(), true);
+        assert_eq!(Session::command_names().contains(&"rjson"), true);
+    }
+
+    #[test]
+    fn show_stats_and_diff() {
+        let output = SharedOutput::default();
+        let mut session = Session::with_output(Box::new(output.clone()));
+        session.insert(1, CubeList::from(vec![vec![1, 1, -1, 0], vec![0, 0, 0, 1]]));
+        session.insert(
+            2,
+            CubeList::from(vec![vec![0, 0, 0, 1], vec![0, -1, 0, -1]]),
+        );
+        session.handle_command("show 1", "test", 1).unwrap();
+        assert_eq!(output.take(), "a b c' + d\n");
+        session.handle_command("stats 1", "test", 2).unwrap();
+        assert_eq!(
+            output.take(),
+            "cubes: 2\nliterals: 4\nsupport: 4 of 4 variables\n\
+             unate: a b c d\nbinate: none\nminterms: 9\n"
+        );
+        session.handle_command("diff 1 2", "test", 3).unwrap();
+        assert_eq!(output.take(), "< a b c'\n> b' d'\n");
+
+        session.handle_command("stats", "test", 4).unwrap();
+        assert_eq!(
+            output.take().lines().take(3).collect::<Vec<&str>>(),
+            vec![
+                "tautology cache: 0 hits, 0 misses, 0 entries",
+                "complement cache: 0 hits, 0 misses, 0 entries",
+                "rule don't care row: 0"
+            ]
+        );
+        session.insert(
+            3,
+            CubeList::from(vec![vec![1, 1, 0], vec![-1, 0, 1], vec![0, -1, -1]]),
+        );
+        session.handle_command("! 4 3", "test", 5).unwrap();
+        session.handle_command("! 4 3", "test", 6).unwrap();
+        session.handle_command("stats", "test", 7).unwrap();
+        assert_eq!(
+            output.take().lines().nth(1),
+            Some("complement cache: 1 hits, 3 misses, 3 entries")
+        );
+        session.handle_command("t 2", "test", 8).unwrap();
+        session.handle_command("set cache off", "test", 9).unwrap();
+        session.handle_command("stats", "test", 10).unwrap();
+        let stats = output.take();
+        assert_eq!(
+            stats.starts_with("2 is not a tautology\ncache: off\n"),
+            true
+        );
+        assert_eq!(stats.contains("rule minterm count: 1\n"), true);
+    }
+
+    #[test]
+    fn command_errors() {
+        let mut session = Session::with_output(Box::new(io::sink()));
+        match session.handle_command("! 1 2", "test", 4) {
+            Err(UrpError::UnknownFunction(2)) => {}
+            result => panic!("expected an unknown function, found {:?}", result),
+        }
+        match session.handle_command("& 1 x 2", "test", 5) {
+            Err(UrpError::Parse { line, column, .. }) => assert_eq!((line, column), (5, 5)),
+            result => panic!("expected a parse error, found {:?}", result),
+        }
+        match session.handle_command("set split best", "test", 7) {
+            Err(UrpError::Parse { column, .. }) => assert_eq!(column, 11),
+            result => panic!("expected a parse error, found {:?}", result),
+        }
+        match session.handle_command("set parallel maybe", "test", 7) {
+            Err(UrpError::Parse { column, .. }) => assert_eq!(column, 14),
+            result => panic!("expected a parse error, found {:?}", result),
+        }
+        session.insert(
+            3,
+            CubeList::from(vec![vec![1, 1, 0], vec![-1, 0, 1], vec![0, -1, -1]]),
+        );
+        session.handle_command("set depth 0", "test", 8).unwrap();
+        match session.handle_command("! 4 3", "test", 9) {
+            Err(UrpError::LimitExceeded(Limit::Depth(0))) => {}
+            result => panic!("expected the depth limit, found {:?}", result),
+        }
+        session.handle_command("set depth off", "test", 10).unwrap();
+        session.cancel_token().cancel();
+        match session.handle_command("! 4 3", "test", 11) {
+            Err(UrpError::LimitExceeded(Limit::Cancelled)) => {}
+            result => panic!("expected a cancellation, found {:?}", result),
+        }
+        // The next command is not cancelled
+        assert_eq!(session.handle_command("! 4 3", "test", 12).unwrap(), true);
+        match session.handle_command("& 1", "test", 6) {
+            Err(UrpError::Parse { line, column, .. }) => assert_eq!((line, column), (6, 4)),
+            result => panic!("expected a parse error, found {:?}", result),
+        }
+    }
+
+    #[test]
+    fn scripts() {
+        let output = SharedOutput(Rc::new(RefCell::new(vec![])));
+        let mut session = Session::with_output(Box::new(output.clone()));
+        session.insert(1, CubeList::from(vec![vec![1, 0]]));
+        session.insert(2, CubeList::from(vec![vec![-1, 0]]));
+        let script = [
+            "# Complements each function",
+            "for f in 1..2",
+            "  ! 1$f $f # the complement is stored as 11 and 12",
+            "  echo complemented $f",
+            "end",
+            "+ 3 1 2",
+            "if taut 3 then",
+            "  echo 3 is a tautology",
+            "else",
+            "  echo 3 is not a tautology",
+            "end",
+            "let g 11",
+            "assert equiv $g 2",
+            "assert not taut 1",
+        ];
+        for (i, line) in script.iter().enumerate() {
+            assert_eq!(session.handle_command(line, "test", i + 1).unwrap(), true);
+            assert_eq!(
+                session.in_block(),
+                (1..4).contains(&i) || (6..10).contains(&i)
+            );
+        }
+        assert_eq!(session.finish().is_ok(), true);
+        assert_eq!(
+            output.take(),
+            "complemented 1\ncomplemented 2\n3 is a tautology\n"
+        );
+        assert_eq!(session.get(12).unwrap() == session.get(1).unwrap(), true);
+        match session.handle_command("assert taut 1", "test", 15) {
+            Err(UrpError::AssertionFailed { line, message, .. }) => {
+                assert_eq!((line, message.as_str()), (15, "taut 1"))
+            }
+            result => panic!("expected a failed assertion, found {:?}", result),
+        }
+    }
+
+    #[test]
+    fn script_errors() {
+        let mut session = Session::with_output(Box::new(io::sink()));
+        match session.handle_command("echo $x", "test", 1) {
+            Err(UrpError::Parse { column, .. }) => assert_eq!(column, 6),
+            result => panic!("expected a parse error, found {:?}", result),
+        }
+        match session.handle_command("end", "test", 2) {
+            Err(UrpError::Parse { column, .. }) => assert_eq!(column, 1),
+            result => panic!("expected a parse error, found {:?}", result),
+        }
+        // The first error stops the block, before the values of a long range
+        session
+            .handle_command("for f in 1 2..4000000000", "test", 3)
+            .unwrap();
+        session.handle_command("t $f", "test", 4).unwrap();
+        match session.handle_command("end", "test", 5) {
+            Err(UrpError::UnknownFunction(1)) => {}
+            result => panic!("expected an unknown function, found {:?}", result),
+        }
+        // equiv is checked with the limits of the session
+        let minterms = (0..8)
+            .map(|i| {
+                (0..3)
+                    .map(|j| if i >> j & 1 == 1 { 1 } else { -1 })
+                    .collect()
+            })
+            .collect::<Vec<Vec<i32>>>();
+        session.insert(1, CubeList::from(minterms));
+        session.insert(2, CubeList::from(vec![vec![0, 0, 0]]));
+        session.handle_command("set depth 0", "test", 6).unwrap();
+        match session.handle_command("assert equiv 1 2", "test", 7) {
+            Err(UrpError::LimitExceeded(Limit::Depth(0))) => {}
+            result => panic!("expected the depth limit, found {:?}", result),
+        }
+        session.handle_command("set depth off", "test", 8).unwrap();
+        session
+            .handle_command("assert equiv 1 2", "test", 9)
+            .unwrap();
+        session.handle_command("if taut 1 then", "test", 6).unwrap();
+        match session.finish() {
+            Err(UrpError::Parse { line, .. }) => assert_eq!(line, 6),
+            result => panic!("expected a parse error, found {:?}", result),
+        }
+        assert_eq!(session.in_block(), false);
+    }
+
+    #[test]
+    fn include() {
+        let dir = std::env::temp_dir().join(format!("urp_include_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("inner.txt"), "echo included\n! 2 1\n").unwrap();
+        let output = SharedOutput(Rc::new(RefCell::new(vec![])));
+        let mut session = Session::with_output(Box::new(output.clone()));
+        session.set_dir(&dir);
+        session.insert(1, CubeList::from(vec![vec![1, 0]]));
+        assert_eq!(
+            session
+                .handle_command("include inner.txt", "test", 1)
+                .unwrap(),
+            true
+        );
+        assert_eq!(output.take(), "included\n");
+        assert_eq!(session.get(2).unwrap().len(), 1);
+        std::fs::write(dir.join("loop.txt"), "include loop.txt\n").unwrap();
+        let result = session.handle_command("include loop.txt", "test", 2);
+        std::fs::remove_dir_all(&dir).unwrap();
+        match result {
+            Err(UrpError::Parse { line, .. }) => assert_eq!(line, 1),
+            result => panic!("expected a parse error, found {:?}", result),
+        }
+    }
+
+    #[test]
+    fn json() {
+        let dir = std::env::temp_dir().join(format!("urp_json_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let mut session = Session::with_output(Box::new(io::sink()));
+        session.set_dir(&dir);
+        session.insert(1, CubeList::from(vec![vec![1, 0, -1], vec![0, 1, 0]]));
+        let written = session.handle_command("pjson 1", "test", 1);
+        let read = session.handle_command("rjson 1", "test", 2);
+        std::fs::remove_dir_all(&dir).unwrap();
+        if cfg!(feature = "serde") {
+            assert_eq!(written.unwrap(), true);
+            assert_eq!(read.unwrap(), true);
+            assert_eq!(session.get(1).unwrap().len(), 2);
+        } else {
+            for result in [written, read] {
+                match result {
+                    Err(UrpError::Parse { column, .. }) => assert_eq!(column, 1),
+                    result => panic!("expected a parse error, found {:?}", result),
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn trace() {
+        let dir = std::env::temp_dir().join(format!("urp_trace_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let mut session = Session::with_output(Box::new(io::sink()));
+        session.set_dir(&dir);
+        session.insert(
+            1,
+            CubeList::from(vec![vec![1, 1], vec![-1, 0], vec![0, -1]]),
+        );
+        match session.handle_command("trace out.json", "test", 1) {
+            Err(UrpError::Parse { column, .. }) => assert_eq!(column, 7),
+            result => panic!("expected a parse error, found {:?}", result),
+        }
+        session.handle_command("set trace on", "test", 2).unwrap();
+        session.handle_command("t 1", "test", 3).unwrap();
+        assert_eq!(session.tracer().unwrap().is_empty(), false);
+        session.handle_command("trace out.json", "test", 4).unwrap();
+        session
+            .handle_command("trace out.folded", "test", 5)
+            .unwrap();
+        let json = std::fs::read_to_string(dir.join("out.json")).unwrap();
+        let folded = std::fs::read_to_string(dir.join("out.folded")).unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(json.starts_with("[{\"operation\":\"tautology\""), true);
+        assert_eq!(folded.starts_with("tautology split x1"), true);
+        session.handle_command("set trace off", "test", 6).unwrap();
+        assert_eq!(session.tracer().is_none(), true);
+    }
+
+    #[test]
+    fn gen() {
+        let mut session = Session::with_output(Box::new(io::sink()));
+        session
+            .handle_command("gen 1 8 12 0.4 5", "test", 1)
+            .unwrap();
+        assert_eq!(
+            session.get(1).unwrap().iter().collect::<Vec<_>>(),
+            CubeList::random(8, 12, 0.4, 5).iter().collect::<Vec<_>>()
+        );
+        session
+            .handle_command("gen 2 8 12 0.4 5 unate", "test", 2)
+            .unwrap();
+        assert_eq!(session.get(2).unwrap().is_unate(), true);
+        session
+            .handle_command("gen 3 8 12 0.4 5 support 3 taut", "test", 3)
+            .unwrap();
+        assert_eq!(session.get(3).unwrap().support().len(), 3);
+        assert_eq!(session.get(3).unwrap().is_tautology(), true);
+        match session.handle_command("gen 4 8 12 0.4 5 support", "test", 4) {
+            Err(UrpError::Parse { column, .. }) => assert_eq!(column, 25),
+            result => panic!("expected a parse error, found {:?}", result),
+        }
+        match session.handle_command("gen 4 8 12 0.4 5 binate", "test", 5) {
+            Err(UrpError::Parse { column, .. }) => assert_eq!(column, 18),
+            result => panic!("expected a parse error, found {:?}", result),
+        }
+        // Functions too large to generate are rejected before any Cube is built
+        for command in ["gen 4 100000 1 0.4 5", "gen 4 65536 65536 0.4 5"] {
+            match session.handle_command(command, "test", 6) {
+                Err(UrpError::Parse { column, .. }) => assert_eq!(column, 7),
+                result => panic!("expected a parse error, found {:?}", result),
+            }
+        }
+        assert_eq!(session.get(4).is_none(), true);
+    }
+}
@@ -0,0 +1,341 @@
+use super::{Command, Session};
+use crate::error::UrpError;
+use crate::parse::tokenize;
+use std::fs;
+
+/// The largest number of include commands that can be nested
+const MAX_INCLUDE_DEPTH: usize = 16;
+
+/// Returns the line without its comment, which starts with #
+pub(super) fn strip_comment(line: &str) -> &str {
+    line.split('#').next().unwrap_or("")
+}
+
+/// Returns the first word of a line
+fn first_word(line: &str) -> Option<&str> {
+    line.split_whitespace().next()
+}
+
+/// Returns true if the line starts a for or an if block
+pub(super) fn starts_block(line: &str) -> bool {
+    matches!(first_word(line), Some("for") | Some("if"))
+}
+
+/// Returns the number of blocks left open at the end of the lines
+pub(super) fn open_blocks(lines: &[(usize, String)]) -> usize {
+    let mut depth = 0;
+    for (_, line) in lines {
+        if starts_block(line) {
+            depth += 1;
+        } else if first_word(line) == Some("end") && depth > 0 {
+            depth -= 1;
+        }
+    }
+    depth
+}
+
+/// Returns the error for a block that has no end
+pub(super) fn unterminated_block(source: &str, line_num: usize, line: &str) -> UrpError {
+    UrpError::Parse {
+        source: source.to_string(),
+        line: line_num,
+        column: 1,
+        message: format!(
+            "{} without a matching end",
+            first_word(line).unwrap_or("block")
+        ),
+    }
+}
+
+/// Finds the end of the block that starts at lines[start]
+///
+/// It returns the index of the else line of the block, if it has one,
+/// and the index of the end line
+fn find_block_end(
+    lines: &[(usize, String)],
+    start: usize,
+    source: &str,
+) -> Result<(Option<usize>, usize), UrpError> {
+    let mut depth = 0;
+    let mut else_index = None;
+    for (j, (_, line)) in lines.iter().enumerate().skip(start) {
+        if starts_block(line) {
+            depth += 1;
+        } else if first_word(line) == Some("end") {
+            depth -= 1;
+            if depth == 0 {
+                return Ok((else_index, j));
+            }
+        } else if first_word(line) == Some("else") && depth == 1 {
+            else_index = Some(j);
+        }
+    }
+    let (line_num, line) = &lines[start];
+    Err(unterminated_block(source, *line_num, line))
+}
+
+impl Session {
+    /// Runs lines of a script, which can contain for and if blocks
+    ///
+    /// It returns false if a quit command was run, else it returns true.
+    /// The first error stops the lines
+    pub(super) fn run_lines(
+        &mut self,
+        lines: &[(usize, String)],
+        source: &str,
+    ) -> Result<bool, UrpError> {
+        let mut i = 0;
+        while i < lines.len() {
+            let (line_num, line) = &lines[i];
+            if starts_block(line) {
+                let (else_index, end) = find_block_end(lines, i, source)?;
+                if !self.run_block(lines, i, else_index, end, source)? {
+                    return Ok(false);
+                }
+                i = end + 1;
+            } else {
+                if !self.run_statement(line, source, *line_num)? {
+                    return Ok(false);
+                }
+                i += 1;
+            }
+        }
+        Ok(true)
+    }
+
+    /// Runs the for or if block from lines[start] to lines[end]
+    fn run_block(
+        &mut self,
+        lines: &[(usize, String)],
+        start: usize,
+        else_index: Option<usize>,
+        end: usize,
+        source: &str,
+    ) -> Result<bool, UrpError> {
+        let (line_num, header) = &lines[start];
+        let header = self.substitute(header, source, *line_num)?;
+        let command = Command {
+            source,
+            line: *line_num,
+            words: tokenize(&header).collect(),
+        };
+        if command.words[0].1 == "for" {
+            // for <var> in <values>
+            if let Some(else_index) = else_index {
+                return Err(UrpError::Parse {
+                    source: source.to_string(),
+                    line: lines[else_index].0,
+                    column: 1,
+                    message: "else without a matching if".to_string(),
+                });
+            }
+            command.expect_args(3)?;
+            let (column, word) = command.words[2];
+            if word != "in" {
+                return Err(command.error(column, format!("Expected in, found {}", word)));
+            }
+            let var = command.words[1].1.trim_start_matches('$').to_string();
+            // The ranges are checked before the body runs, and their values
+            // are only produced as the loop reaches them
+            let ranges = (3..command.words.len())
+                .map(|i| command.range(i))
+                .collect::<Result<Vec<_>, UrpError>>()?;
+            let previous = self.vars.remove(&var);
+            let mut result = Ok(true);
+            for value in ranges.into_iter().flatten() {
+                self.vars.insert(var.clone(), value);
+                result = self.run_lines(&lines[start + 1..end], source);
+                if !matches!(result, Ok(true)) {
+                    break;
+                }
+            }
+            match previous {
+                Some(previous) => self.vars.insert(var, previous),
+                None => self.vars.remove(&var),
+            };
+            result
+        } else {
+            // if <condition> then
+            let last = command.words.len() - 1;
+            let (column, word) = command.words[last];
+            if word != "then" {
+                return Err(command.error(column + word.len(), "Expected then".to_string()));
+            }
+            if self.evaluate(&command, 1, last)? {
+                self.run_lines(&lines[start + 1..else_index.unwrap_or(end)], source)
+            } else if let Some(else_index) = else_index {
+                self.run_lines(&lines[else_index + 1..end], source)
+            } else {
+                Ok(true)
+            }
+        }
+    }
+
+    /// Evaluates the condition made of the words from..to of the command
+    ///
+    /// The condition is `taut <f>` or `equiv <f> <g>`, optionally preceded by not
+    fn evaluate(&self, command: &Command, from: usize, to: usize) -> Result<bool, UrpError> {
+        let negate = command.words.get(from).map(|(_, word)| *word) == Some("not");
+        let from = if negate { from + 1 } else { from };
+        let (column, word) = match command.words.get(from) {
+            Some(word) if from < to => *word,
+            _ => {
+                let (column, word) = command.words[to - 1];
+                return Err(
+                    command.error(column + word.len(), "Expected taut or equiv".to_string())
+                );
+            }
+        };
+        let num_args = match word {
+            "taut" => 1,
+            "equiv" => 2,
+            _ => {
+                return Err(command.error(column, format!("Expected taut or equiv, found {}", word)))
+            }
+        };
+        if to - from != num_args + 1 {
+            let (column, word) = command.words[to - 1];
+            return Err(command.error(
+                column + word.len(),
+                format!("Expected {} arguments after {}", num_args, word),
+            ));
+        }
+        let result = if num_args == 1 {
+            self.is_tautology(command.arg(from + 1)?)?
+        } else {
+            self.is_equivalent(command.arg(from + 1)?, command.arg(from + 2)?)?
+        };
+        Ok(result != negate)
+    }
+
+    /// Replaces each $var in the line by the value of the variable var,
+    /// whose name is made of letters, digits and underscores
+    fn substitute(&self, line: &str, source: &str, line_num: usize) -> Result<String, UrpError> {
+        let mut result = String::new();
+        let mut rest = line;
+        while let Some(start) = rest.find('$') {
+            result.push_str(&rest[..start]);
+            let name = &rest[start + 1..];
+            let end = name
+                .find(|c: char| !c.is_ascii_alphanumeric() && c != '_')
+                .unwrap_or(name.len());
+            match self.vars.get(&name[..end]) {
+                Some(value) => result.push_str(value),
+                None => {
+                    return Err(UrpError::Parse {
+                        source: source.to_string(),
+                        line: line_num,
+                        column: line.len() - rest.len() + start + 1,
+                        message: format!("Variable {} is not set", &name[..end]),
+                    })
+                }
+            }
+            rest = &name[end..];
+        }
+        result.push_str(rest);
+        Ok(result)
+    }
+
+    /// Runs a line that is not part of a for or if block
+    pub(super) fn run_statement(
+        &mut self,
+        line: &str,
+        source: &str,
+        line_num: usize,
+    ) -> Result<bool, UrpError> {
+        let line = self.substitute(line, source, line_num)?;
+        let command = Command {
+            source,
+            line: line_num,
+            words: tokenize(&line).collect(),
+        };
+        let (column, name) = match command.words.first() {
+            Some(word) => *word,
+            None => return Ok(true),
+        };
+        match name {
+            "echo" => {
+                // Prints the rest of the line
+                let text = command
+                    .words
+                    .get(1)
+                    .map_or("", |(column, _)| line[column - 1..].trim_end());
+                self.print(text.to_string())?;
+            }
+            "assert" => {
+                // Stops with an error if the condition does not hold
+                if !self.evaluate(&command, 1, command.words.len())? {
+                    return Err(UrpError::AssertionFailed {
+                        source: source.to_string(),
+                        line: line_num,
+                        message: line[command.words[1].0 - 1..].trim_end().to_string(),
+                    });
+                }
+            }
+            "let" => {
+                // Sets a variable
+                command.expect_args(2)?;
+                let var = command.words[1].1.trim_start_matches('$').to_string();
+                self.vars.insert(var, command.words[2].1.to_string());
+            }
+            "include" => {
+                // Runs the commands of another file
+                command.expect_args(1)?;
+                return self.include(&command);
+            }
+            "end" | "else" => {
+                return Err(command.error(column, format!("{} without a matching for or if", name)));
+            }
+            _ => return self.run_command(&command),
+        }
+        Ok(true)
+    }
+
+    /// Runs the commands of the file named by the include command
+    fn include(&mut self, command: &Command) -> Result<bool, UrpError> {
+        let (column, file) = command.words[1];
+        if self.include_depth >= MAX_INCLUDE_DEPTH {
+            return Err(command.error(
+                column,
+                format!("More than {} nested includes", MAX_INCLUDE_DEPTH),
+            ));
+        }
+        let path = self.dir.join(file).to_string_lossy().into_owned();
+        let contents = fs::read_to_string(&path).map_err(|error| UrpError::Io {
+            path: path.clone(),
+            error,
+        })?;
+        let lines: Vec<(usize, String)> = contents
+            .lines()
+            .enumerate()
+            .map(|(i, line)| (i + 1, strip_comment(line).to_string()))
+            .collect();
+        self.include_depth += 1;
+        let result = self.run_lines(&lines, &path);
+        self.include_depth -= 1;
+        result
+    }
+}
+
+impl<'a> Command<'a> {
+    /// Parses the argument at position i as a number or a range of
+    /// numbers like 1..5, which includes both ends
+    fn range(&self, i: usize) -> Result<Box<dyn Iterator<Item = String>>, UrpError> {
+        let (column, word) = self.words[i];
+        match word.split_once("..") {
+            Some((first, last)) => {
+                let parse = |value: &str| {
+                    value.parse::<u32>().map_err(|_| {
+                        self.error(
+                            column,
+                            format!("Expected a range of numbers, found {}", word),
+                        )
+                    })
+                };
+                let values = parse(first)?..=parse(last)?;
+                Ok(Box::new(values.map(|value| value.to_string())))
+            }
+            None => Ok(Box::new(std::iter::once(word.to_string()))),
+        }
+    }
+}
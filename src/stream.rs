@@ -0,0 +1,583 @@
+//! Streaming of Cubes from and to PCN and PLA files
+//!
+//! CubeList::read_from_file and CubeList::read_pla hold every Cube of a
+//! file in memory. A CubeReader parses a file one line at a time and
+//! yields its Cubes as they are read, and a CubeWriter writes Cubes as
+//! they come, so that a file larger than the memory can be processed Cube
+//! by Cube. The functions of this module perform the operations that do
+//! not need the whole function at once on such streams of Cubes.
+
+use super::cube::{Cube, Literal};
+use super::cubelist::CubeList;
+use super::error::UrpError;
+use super::pcn;
+use super::pla;
+use std::fs::File;
+use std::io::{BufRead, BufReader, BufWriter, Seek, SeekFrom, Write};
+
+/// The width of the number of cubes written by a PCN CubeWriter, which
+/// holds any u64 so that it can be overwritten once the Cubes are counted
+const PCN_COUNT_WIDTH: usize = 20;
+
+/// Parser is the line parser of the format of a stream
+enum Parser {
+    Pcn(pcn::Parser),
+    Pla(pla::Parser),
+}
+
+impl Parser {
+    fn num_var(&self) -> Option<usize> {
+        match self {
+            Parser::Pcn(parser) => parser.num_var(),
+            Parser::Pla(parser) => parser.num_var(),
+        }
+    }
+
+    fn line(
+        &mut self,
+        line_num: usize,
+        line: &str,
+        error: &mut dyn FnMut(usize, usize, String),
+    ) -> Option<Cube> {
+        match self {
+            Parser::Pcn(parser) => parser.line(line_num, line, error),
+            Parser::Pla(parser) => parser.line(line_num, line, error),
+        }
+    }
+
+    /// Returns true if the lines after the last one parsed are ignored
+    fn ended(&self) -> bool {
+        match self {
+            Parser::Pcn(_) => false,
+            Parser::Pla(parser) => parser.ended(),
+        }
+    }
+
+    fn finish(&self, error: &mut dyn FnMut(usize, usize, String)) {
+        match self {
+            Parser::Pcn(parser) => parser.finish(error),
+            Parser::Pla(parser) => parser.finish(error),
+        }
+    }
+}
+
+/// CubeReader is an iterator over the Cubes of a PCN or PLA file, which
+/// reads the file one line at a time. Only the ON-set Cubes of a PLA file
+/// are yielded
+///
+/// The problems of the file are reported as in pcn::parse and pla::parse,
+/// but only the first one is yielded, after which the iteration stops.
+/// Problems found at the end of the file, like fewer Cubes than declared,
+/// are only yielded once every Cube was read
+///
+/// ```
+/// use urp::stream::CubeReader;
+///
+/// let contents = "3\n2\n2 1 -3\n1 2\n";
+/// let reader = CubeReader::pcn("example.pcn", contents.as_bytes()).unwrap();
+/// assert_eq!(reader.num_var(), 3);
+/// assert_eq!(reader.count(), 2);
+/// ```
+pub struct CubeReader<R> {
+    source: String,
+    reader: R,
+    parser: Parser,
+    line: String,
+    line_num: usize,
+    pending: Option<Cube>,
+    done: bool,
+}
+
+impl CubeReader<BufReader<File>> {
+    /// This function opens a PCN file, or a PLA file if its name ends
+    /// with .pla, and returns a CubeReader over its Cubes
+    pub fn open(file: &str) -> Result<Self, UrpError> {
+        let reader = File::open(file).map_err(|error| UrpError::Io {
+            path: file.to_string(),
+            error,
+        })?;
+        if file.ends_with(".pla") {
+            CubeReader::pla(file, BufReader::new(reader))
+        } else {
+            CubeReader::pcn(file, BufReader::new(reader))
+        }
+    }
+}
+
+impl<R: BufRead> CubeReader<R> {
+    /// This function returns a CubeReader over the Cubes of PCN contents.
+    /// The source names the contents in the errors
+    ///
+    /// The number of variables is read right away, so a problem on the
+    /// first line is returned here
+    pub fn pcn(source: &str, reader: R) -> Result<Self, UrpError> {
+        CubeReader::new(source, reader, Parser::Pcn(pcn::Parser::new()))
+    }
+
+    /// This function returns a CubeReader over the ON-set Cubes of single
+    /// output PLA contents. The source names the contents in the errors
+    ///
+    /// The contents are read up to .i or the first Cube to find the number
+    /// of variables, so a problem before them is returned here
+    pub fn pla(source: &str, reader: R) -> Result<Self, UrpError> {
+        CubeReader::new(source, reader, Parser::Pla(pla::Parser::new()))
+    }
+
+    /// Returns a CubeReader that has read up to the number of variables
+    fn new(source: &str, reader: R, parser: Parser) -> Result<Self, UrpError> {
+        let mut cube_reader = CubeReader {
+            source: source.to_string(),
+            reader,
+            parser,
+            line: String::new(),
+            line_num: 0,
+            pending: None,
+            done: false,
+        };
+        while cube_reader.parser.num_var().is_none() && !cube_reader.done {
+            cube_reader.pending = cube_reader.read_cube()?;
+        }
+        Ok(cube_reader)
+    }
+
+    /// Returns the number of variables of the Cubes
+    pub fn num_var(&self) -> usize {
+        self.parser.num_var().unwrap_or(0)
+    }
+
+    /// This function reads the remaining Cubes into a CubeList, or returns
+    /// the first problem of the contents
+    pub fn into_cubelist(self) -> Result<CubeList, UrpError> {
+        let mut cubelist = CubeList::new(self.num_var());
+        for cube in self {
+            cubelist.add_cube(cube?);
+        }
+        Ok(cubelist)
+    }
+
+    /// Reads lines up to the next Cube, and returns None once the contents
+    /// end. The reader is done after the end or a problem
+    fn read_cube(&mut self) -> Result<Option<Cube>, UrpError> {
+        let mut errors = Vec::new();
+        let mut cube = None;
+        while cube.is_none() && errors.is_empty() && !self.done {
+            self.line.clear();
+            let read = self.reader.read_line(&mut self.line);
+            match read {
+                Ok(0) => {
+                    self.done = true;
+                    self.parser
+                        .finish(&mut |line, column, message| errors.push((line, column, message)));
+                }
+                Ok(_) => {
+                    self.line_num += 1;
+                    let line = match self.line.strip_suffix('\n') {
+                        Some(line) => line.strip_suffix('\r').unwrap_or(line),
+                        None => &self.line,
+                    };
+                    cube = self
+                        .parser
+                        .line(self.line_num, line, &mut |line, column, message| {
+                            errors.push((line, column, message))
+                        });
+                    if self.parser.ended() {
+                        self.done = true;
+                        self.parser.finish(&mut |line, column, message| {
+                            errors.push((line, column, message))
+                        });
+                    }
+                }
+                Err(error) => {
+                    self.done = true;
+                    return Err(UrpError::Io {
+                        path: self.source.clone(),
+                        error,
+                    });
+                }
+            }
+        }
+        match errors.into_iter().next() {
+            Some((line, column, message)) => {
+                self.done = true;
+                Err(UrpError::Parse {
+                    source: self.source.clone(),
+                    line,
+                    column,
+                    message,
+                })
+            }
+            None => Ok(cube),
+        }
+    }
+}
+
+impl<R: BufRead> Iterator for CubeReader<R> {
+    type Item = Result<Cube, UrpError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(cube) = self.pending.take() {
+            return Some(Ok(cube));
+        }
+        if self.done {
+            return None;
+        }
+        self.read_cube().transpose()
+    }
+}
+
+/// CubeWriter writes Cubes to a PCN or a single output PLA file as they
+/// come, without holding them in memory
+///
+/// The number of Cubes of a PCN file is written once they are counted,
+/// by finish. A CubeWriter dropped before finish leaves a PCN file that
+/// declares no Cube
+pub struct CubeWriter<W: Write + Seek> {
+    target: String,
+    writer: W,
+    num_var: usize,
+    pla: bool,
+    count: u64,
+    count_position: u64,
+}
+
+impl CubeWriter<BufWriter<File>> {
+    /// This function creates a PCN file, or a PLA file if its name ends
+    /// with .pla, and returns a CubeWriter of Cubes of num_var variables
+    pub fn create(file: &str, num_var: usize) -> Result<Self, UrpError> {
+        let writer = File::create(file).map_err(|error| UrpError::Io {
+            path: file.to_string(),
+            error,
+        })?;
+        if file.ends_with(".pla") {
+            CubeWriter::pla(file, BufWriter::new(writer), num_var)
+        } else {
+            CubeWriter::pcn(file, BufWriter::new(writer), num_var)
+        }
+    }
+}
+
+impl<W: Write + Seek> CubeWriter<W> {
+    /// This function returns a CubeWriter that writes Cubes of num_var
+    /// variables in the PCN format. The target names the writer in the errors
+    ///
+    /// The number of Cubes is written padded with spaces, which
+    /// CubeList::read_from_file accepts
+    pub fn pcn(target: &str, writer: W, num_var: usize) -> Result<Self, UrpError> {
+        let mut cube_writer = CubeWriter::new(target, writer, num_var, false);
+        writeln!(cube_writer.writer, "{}", num_var).map_err(|error| cube_writer.io_error(error))?;
+        cube_writer.count_position = cube_writer
+            .writer
+            .stream_position()
+            .map_err(|error| cube_writer.io_error(error))?;
+        writeln!(cube_writer.writer, "{:<1$}", 0, PCN_COUNT_WIDTH)
+            .map_err(|error| cube_writer.io_error(error))?;
+        Ok(cube_writer)
+    }
+
+    /// This function returns a CubeWriter that writes Cubes of num_var
+    /// variables as the ON-set of a single output PLA file, without .p.
+    /// The target names the writer in the errors
+    pub fn pla(target: &str, writer: W, num_var: usize) -> Result<Self, UrpError> {
+        let mut cube_writer = CubeWriter::new(target, writer, num_var, true);
+        write!(cube_writer.writer, ".i {}\n.o 1\n", num_var)
+            .map_err(|error| cube_writer.io_error(error))?;
+        Ok(cube_writer)
+    }
+
+    fn new(target: &str, writer: W, num_var: usize, pla: bool) -> Self {
+        CubeWriter {
+            target: target.to_string(),
+            writer,
+            num_var,
+            pla,
+            count: 0,
+            count_position: 0,
+        }
+    }
+
+    fn io_error(&self, error: std::io::Error) -> UrpError {
+        UrpError::Io {
+            path: self.target.clone(),
+            error,
+        }
+    }
+
+    /// This function writes a Cube, which must have num_var variables
+    pub fn write(&mut self, cube: &Cube) -> Result<(), UrpError> {
+        if cube.len() != self.num_var {
+            return Err(UrpError::VarCountMismatch {
+                expected: self.num_var,
+                found: cube.len(),
+            });
+        }
+        let written = if self.pla {
+            writeln!(self.writer, "{} 1", cube.to_pla_string())
+        } else {
+            writeln!(self.writer, "{}", cube)
+        };
+        written.map_err(|error| self.io_error(error))?;
+        self.count += 1;
+        Ok(())
+    }
+
+    /// This function writes every Cube of a stream, and stops at the first
+    /// problem of the stream
+    pub fn write_all<I>(&mut self, cubes: I) -> Result<(), UrpError>
+    where
+        I: IntoIterator<Item = Result<Cube, UrpError>>,
+    {
+        for cube in cubes {
+            self.write(&cube?)?;
+        }
+        Ok(())
+    }
+
+    /// Returns the number of Cubes written
+    pub fn count(&self) -> u64 {
+        self.count
+    }
+
+    /// This function ends the file, writes the number of Cubes of a PCN
+    /// file and returns the writer
+    pub fn finish(mut self) -> Result<W, UrpError> {
+        let finished = if self.pla {
+            writeln!(self.writer, ".e")
+        } else {
+            self.writer
+                .seek(SeekFrom::Start(self.count_position))
+                .and_then(|_| write!(self.writer, "{:<1$}", self.count, PCN_COUNT_WIDTH))
+                .and_then(|_| self.writer.seek(SeekFrom::End(0)).map(|_| ()))
+        };
+        finished
+            .and_then(|_| self.writer.flush())
+            .map_err(|error| self.io_error(error))?;
+        Ok(self.writer)
+    }
+}
+
+/// This function returns the Cubes of the OR of the functions read by
+/// both readers, which has the larger of their numbers of variables
+///
+/// Unlike CubeList::or, Cubes present in both functions are not removed,
+/// as that would need every Cube in memory
+pub fn or<A: BufRead, B: BufRead>(
+    reader_a: CubeReader<A>,
+    reader_b: CubeReader<B>,
+) -> impl Iterator<Item = Result<Cube, UrpError>> {
+    let num_var = reader_a.num_var().max(reader_b.num_var());
+    reader_a.chain(reader_b).map(move |cube| {
+        let cube = cube?;
+        Ok(if cube.len() < num_var {
+            cube.extend_vars(num_var)
+        } else {
+            cube
+        })
+    })
+}
+
+/// This function returns the Cubes of the positive cofactor of a stream
+/// of Cubes with respect to the variable var_num, or of the negative one
+/// if positive is false, like CubeList::cofactor
+pub fn cofactor<I>(
+    cubes: I,
+    var_num: usize,
+    positive: bool,
+) -> impl Iterator<Item = Result<Cube, UrpError>>
+where
+    I: IntoIterator<Item = Result<Cube, UrpError>>,
+{
+    let opposite = if positive {
+        Literal::Negative
+    } else {
+        Literal::Positive
+    };
+    cubes.into_iter().filter_map(move |cube| {
+        let mut cube = match cube {
+            Ok(cube) => cube,
+            Err(error) => return Some(Err(error)),
+        };
+        match cube.get_literal(var_num) {
+            Ok(literal) if literal == opposite => None,
+            Ok(_) => {
+                cube.set_literal(var_num, Literal::Dontcare);
+                Some(Ok(cube))
+            }
+            Err(error) => Some(Err(error)),
+        }
+    })
+}
+
+/// This function returns the Cubes of a stream that are not contained in
+/// any Cube of the cover, which is held in memory. The Cubes removed do
+/// not change the OR of the stream and the cover
+///
+/// The Cubes of the stream are not checked against each other, as
+/// CubeList::single_cube_containment does, since that would need every
+/// Cube in memory
+pub fn remove_contained<'a, I>(
+    cubes: I,
+    cover: &'a CubeList,
+) -> impl Iterator<Item = Result<Cube, UrpError>> + 'a
+where
+    I: IntoIterator<Item = Result<Cube, UrpError>>,
+    I::IntoIter: 'a,
+{
+    cubes.into_iter().filter(move |cube| match cube {
+        Ok(cube) => !cover.iter().any(|cube_x| cube_x.contains(cube)),
+        Err(_) => true,
+    })
+}
+
+#[cfg(test)]
+#[allow(clippy::bool_assert_comparison)]
+mod test {
+    use super::*;
+    use std::io::Cursor;
+
+    /// Returns the Cubes of a stream, or its first problem
+    fn collect<I: Iterator<Item = Result<Cube, UrpError>>>(cubes: I) -> Vec<Cube> {
+        cubes.collect::<Result<Vec<Cube>, UrpError>>().unwrap()
+    }
+
+    #[test]
+    fn read_pcn() {
+        let contents = "3\n2\r\n2 1 -3\n1 2";
+        let reader = CubeReader::pcn("test.pcn", contents.as_bytes()).unwrap();
+        assert_eq!(reader.num_var(), 3);
+        assert_eq!(
+            collect(reader),
+            vec![Cube::from(vec![1, 0, -1]), Cube::from(vec![0, 1, 0])]
+        );
+
+        let reader = CubeReader::pcn("test.pcn", "3\n3\n1 1\n2 1 x\n1 2\n".as_bytes()).unwrap();
+        let cubes: Vec<_> = reader.collect();
+        assert_eq!(cubes.len(), 2);
+        match &cubes[1] {
+            Err(UrpError::Parse { line, column, .. }) => assert_eq!((*line, *column), (4, 5)),
+            result => panic!("expected a parse error, found {:?}", result),
+        }
+        // The missing Cube is only found at the end
+        let mut reader = CubeReader::pcn("test.pcn", "3\n2\n1 1\n".as_bytes()).unwrap();
+        assert_eq!(reader.next().unwrap().is_ok(), true);
+        match reader.next() {
+            Some(Err(UrpError::Parse { line, .. })) => assert_eq!(line, 4),
+            result => panic!("expected a parse error, found {:?}", result),
+        }
+        assert_eq!(reader.next().is_none(), true);
+        assert_eq!(CubeReader::pcn("test.pcn", "x\n".as_bytes()).is_err(), true);
+        assert_eq!(CubeReader::pcn("test.pcn", "".as_bytes()).is_err(), true);
+    }
+
+    #[test]
+    fn read_pla() {
+        let contents = "# example\n.o 1\n1-0 1\n-11 0\n01- 1\n.e\nignored\n";
+        let reader = CubeReader::pla("test.pla", contents.as_bytes()).unwrap();
+        assert_eq!(reader.num_var(), 3);
+        let cubelist = reader.into_cubelist().unwrap();
+        assert_eq!(cubelist.len(), 2);
+        assert_eq!(cubelist.contains_cube(&Cube::from(vec![-1, 1, 0])), true);
+
+        let reader = CubeReader::pla("test.pla", ".i 3\n.p 2\n1-0 1\n".as_bytes()).unwrap();
+        match reader.into_cubelist() {
+            Err(UrpError::Parse { line, .. }) => assert_eq!(line, 2),
+            result => panic!("expected a parse error, found {:?}", result),
+        }
+        assert_eq!(
+            CubeReader::pla("test.pla", ".o 2\n".as_bytes()).is_err(),
+            true
+        );
+    }
+
+    #[test]
+    fn write() {
+        let cubelist = CubeList::from(vec![vec![1, 0, -1], vec![0, 1, 0]]);
+        let mut writer = CubeWriter::pcn("test.pcn", Cursor::new(vec![]), 3).unwrap();
+        writer.write_all(cubelist.iter().cloned().map(Ok)).unwrap();
+        assert_eq!(writer.count(), 2);
+        assert_eq!(
+            writer.write(&Cube::from(vec![1])).is_err(),
+            true,
+            "a Cube of another number of variables"
+        );
+        let contents = String::from_utf8(writer.finish().unwrap().into_inner()).unwrap();
+        assert_eq!(contents.lines().nth(1).unwrap().trim_end(), "2");
+        let read = pcn::parse("test.pcn", &contents).unwrap();
+        assert_eq!(
+            read.iter().collect::<Vec<_>>(),
+            cubelist.iter().collect::<Vec<_>>()
+        );
+
+        let mut writer = CubeWriter::pla("test.pla", Cursor::new(vec![]), 3).unwrap();
+        writer.write_all(cubelist.iter().cloned().map(Ok)).unwrap();
+        let contents = String::from_utf8(writer.finish().unwrap().into_inner()).unwrap();
+        assert_eq!(contents, ".i 3\n.o 1\n1-0 1\n-1- 1\n.e\n");
+        assert_eq!(pla::parse("test.pla", &contents).unwrap().len(), 2);
+    }
+
+    #[test]
+    fn files() {
+        let dir = std::env::temp_dir();
+        let input = dir.join("urp_stream_input.pla");
+        let output = dir.join("urp_stream_output.pcn");
+        let (input, output) = (input.to_str().unwrap(), output.to_str().unwrap());
+        let cubelist = CubeList::random(8, 100, 0.4, 5);
+        cubelist.write_pla(input).unwrap();
+
+        let reader = CubeReader::open(input).unwrap();
+        let mut writer = CubeWriter::create(output, reader.num_var()).unwrap();
+        writer.write_all(reader).unwrap();
+        writer.finish().unwrap();
+        let read = CubeList::read_from_file(output).unwrap();
+        assert_eq!(
+            read.iter().collect::<Vec<_>>(),
+            cubelist.iter().collect::<Vec<_>>()
+        );
+        std::fs::remove_file(input).unwrap();
+        std::fs::remove_file(output).unwrap();
+        assert_eq!(CubeReader::open(input).is_err(), true);
+    }
+
+    #[test]
+    fn operations() {
+        let a = "3\n2\n2 1 -3\n1 2\n";
+        let b = ".i 4\n1--1 1\n0--- 1\n";
+        let reader_a = CubeReader::pcn("a.pcn", a.as_bytes()).unwrap();
+        let reader_b = CubeReader::pla("b.pla", b.as_bytes()).unwrap();
+        let cubes = collect(or(reader_a, reader_b));
+        assert_eq!(
+            cubes,
+            vec![
+                Cube::from(vec![1, 0, -1, 0]),
+                Cube::from(vec![0, 1, 0, 0]),
+                Cube::from(vec![1, 0, 0, 1]),
+                Cube::from(vec![-1, 0, 0, 0]),
+            ]
+        );
+
+        let cubelist = CubeList::from(vec![vec![1, 0, -1], vec![0, 1, 0], vec![-1, -1, 0]]);
+        let (pos_cofactor, neg_cofactor) = cubelist.cofactor(1);
+        let contents = cubelist.to_string();
+        for (positive, expected) in [(true, pos_cofactor), (false, neg_cofactor)] {
+            let reader = CubeReader::pcn("a.pcn", contents.as_bytes()).unwrap();
+            assert_eq!(
+                collect(cofactor(reader, 1, positive)),
+                expected.iter().cloned().collect::<Vec<_>>()
+            );
+        }
+        let reader = CubeReader::pcn("a.pcn", a.as_bytes()).unwrap();
+        assert_eq!(cofactor(reader, 4, true).next().unwrap().is_err(), true);
+
+        let cover = CubeList::from(vec![vec![1, 0, 0]]);
+        let reader = CubeReader::pcn("a.pcn", contents.as_bytes()).unwrap();
+        assert_eq!(
+            collect(remove_contained(reader, &cover)),
+            vec![Cube::from(vec![0, 1, 0]), Cube::from(vec![-1, -1, 0])]
+        );
+        // A problem of the stream is kept
+        let reader = CubeReader::pcn("a.pcn", "3\n2\n1 1\n".as_bytes()).unwrap();
+        assert_eq!(
+            remove_contained(reader, &cover).last().unwrap().is_err(),
+            true
+        );
+    }
+}
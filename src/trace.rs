@@ -0,0 +1,318 @@
+//! Traces of the URP recursion
+//!
+//! A Tracer given to complement_with or is_tautology_with in UrpOptions
+//! records a node for every cover met by the recursion: its depth, its
+//! number of Cubes, the step that handled it, the variable it was split
+//! on and the time spent on it. The recorded tree can be written as JSON,
+//! or as folded stacks, the input of flame graph tools such as
+//! flamegraph.pl or inferno, to see where the time goes.
+
+use super::error::UrpError;
+use super::rules::Rule;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Operation is the operation that met a traced cover
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Operation {
+    Complement,
+    Tautology,
+}
+
+impl fmt::Display for Operation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Operation::Complement => write!(f, "complement"),
+            Operation::Tautology => write!(f, "tautology"),
+        }
+    }
+}
+
+/// Step is the way the recursion handled a traced cover
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Step {
+    /// The cover has no Cube
+    Empty,
+    /// The cover has a single Cube, which is complemented directly
+    SingleCube,
+    /// The result for the cover was found in the cache
+    Cached,
+    /// One of the rules of is_tautology, which are also used by complement
+    /// for Cubes without literals, components and splits
+    Rule(Rule),
+}
+
+impl fmt::Display for Step {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Step::Empty => write!(f, "empty"),
+            Step::SingleCube => write!(f, "single cube"),
+            Step::Cached => write!(f, "cached"),
+            Step::Rule(rule) => write!(f, "{}", rule),
+        }
+    }
+}
+
+/// TraceNode is a cover met by the recursion
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TraceNode {
+    /// The number of the node of the cover this one was derived from,
+    /// or None for the function the operation started with
+    pub parent: Option<usize>,
+    pub operation: Operation,
+    /// The depth of the recursion, which is the number of splits above the node
+    pub depth: usize,
+    /// The number of Cubes of the cover
+    pub cubes: usize,
+    /// The step that handled the cover, or None if the recursion stopped
+    /// before, e.g. because a limit was exceeded
+    pub step: Option<Step>,
+    /// The variable the cover was split on
+    pub var: Option<usize>,
+    /// The time spent on the cover, including the nodes below it
+    pub elapsed: Duration,
+}
+
+impl TraceNode {
+    /// Returns the name of the node in a flame graph
+    fn label(&self) -> String {
+        let mut label = self.operation.to_string();
+        if let Some(step) = self.step {
+            label.push_str(&format!(" {}", step));
+        }
+        if let Some(var) = self.var {
+            label.push_str(&format!(" x{}", var));
+        }
+        label
+    }
+}
+
+/// Tracer records the tree of covers met by the URP recursion. It can be
+/// shared by the tasks of the parallel recursion, and keeps the nodes of
+/// every operation it was given to until it is cleared
+///
+/// The nodes are numbered in the order in which the covers are met, so
+/// the parent of a node always comes before it
+#[derive(Debug, Default)]
+pub struct Tracer {
+    nodes: Mutex<Vec<(TraceNode, Instant)>>,
+}
+
+impl Tracer {
+    /// Returns a new Tracer without any node
+    pub fn new() -> Self {
+        Tracer::default()
+    }
+
+    /// Records a new node for a cover and returns its number
+    pub(crate) fn enter(
+        &self,
+        parent: Option<usize>,
+        operation: Operation,
+        depth: usize,
+        cubes: usize,
+    ) -> usize {
+        let mut nodes = self.nodes.lock().unwrap();
+        let node = TraceNode {
+            parent,
+            operation,
+            depth,
+            cubes,
+            step: None,
+            var: None,
+            elapsed: Duration::ZERO,
+        };
+        nodes.push((node, Instant::now()));
+        nodes.len() - 1
+    }
+
+    /// Records the step that handled the cover of a node
+    pub(crate) fn step(&self, node: usize, step: Step) {
+        self.nodes.lock().unwrap()[node].0.step = Some(step);
+    }
+
+    /// Records the variable the cover of a node was split on
+    pub(crate) fn split(&self, node: usize, var: usize) {
+        self.nodes.lock().unwrap()[node].0.var = Some(var);
+    }
+
+    /// Records the time spent on a node, once its cover is handled
+    pub(crate) fn leave(&self, node: usize) {
+        let (node, start) = &mut self.nodes.lock().unwrap()[node];
+        node.elapsed = start.elapsed();
+    }
+
+    /// Returns the recorded nodes
+    pub fn nodes(&self) -> Vec<TraceNode> {
+        let nodes = self.nodes.lock().unwrap();
+        nodes.iter().map(|(node, _)| node.clone()).collect()
+    }
+
+    /// Returns the number of recorded nodes
+    pub fn len(&self) -> usize {
+        self.nodes.lock().unwrap().len()
+    }
+
+    /// Returns true if no node was recorded
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Removes every recorded node
+    pub fn clear(&self) {
+        self.nodes.lock().unwrap().clear();
+    }
+
+    /// This function returns the recorded tree as a JSON array with an
+    /// object for each operation. Each object has the fields operation,
+    /// depth, cubes, step, var and time_us of its node, and the objects
+    /// of the nodes below it in children
+    pub fn to_json(&self) -> String {
+        let nodes = self.nodes();
+        let mut children = vec![vec![]; nodes.len()];
+        let mut roots = vec![];
+        for (i, node) in nodes.iter().enumerate() {
+            match node.parent {
+                Some(parent) => children[parent].push(i),
+                None => roots.push(i),
+            }
+        }
+        let mut json = String::new();
+        write_json_nodes(&mut json, &nodes, &children, &roots);
+        json
+    }
+
+    /// This function returns the recorded tree as folded stacks: a line
+    /// for each path from an operation to a node, with the names of the
+    /// nodes separated by semicolons, followed by the time spent on the
+    /// node itself in microseconds
+    ///
+    /// Nodes with the same path are merged, and the lines are sorted
+    pub fn to_folded(&self) -> String {
+        let nodes = self.nodes();
+        let mut self_time: Vec<Duration> = nodes.iter().map(|node| node.elapsed).collect();
+        let mut stacks: Vec<String> = Vec::with_capacity(nodes.len());
+        for node in &nodes {
+            let stack = match node.parent {
+                Some(parent) => {
+                    self_time[parent] = self_time[parent].saturating_sub(node.elapsed);
+                    format!("{};{}", stacks[parent], node.label())
+                }
+                None => node.label(),
+            };
+            stacks.push(stack);
+        }
+        let mut folded = BTreeMap::new();
+        for (stack, time) in stacks.into_iter().zip(self_time) {
+            *folded.entry(stack).or_insert(0) += time.as_micros();
+        }
+        folded
+            .into_iter()
+            .map(|(stack, time)| format!("{} {}\n", stack, time))
+            .collect()
+    }
+
+    /// This function writes the recorded tree to a file as JSON
+    pub fn write_json(&self, file: &str) -> Result<(), UrpError> {
+        write_file(file, self.to_json())
+    }
+
+    /// This function writes the recorded tree to a file as folded stacks
+    pub fn write_folded(&self, file: &str) -> Result<(), UrpError> {
+        write_file(file, self.to_folded())
+    }
+}
+
+/// Writes the contents to a file
+fn write_file(file: &str, contents: String) -> Result<(), UrpError> {
+    fs::write(file, contents).map_err(|error| UrpError::Io {
+        path: file.to_string(),
+        error,
+    })
+}
+
+/// Appends the JSON array of the given nodes and the nodes below them
+fn write_json_nodes(
+    json: &mut String,
+    nodes: &[TraceNode],
+    children: &[Vec<usize>],
+    ids: &[usize],
+) {
+    json.push('[');
+    for (i, &id) in ids.iter().enumerate() {
+        if i > 0 {
+            json.push(',');
+        }
+        let node = &nodes[id];
+        let option = |value: Option<String>| value.unwrap_or_else(|| "null".to_string());
+        json.push_str(&format!(
+            "{{\"operation\":\"{}\",\"depth\":{},\"cubes\":{},\"step\":{},\"var\":{},\"time_us\":{},\"children\":",
+            node.operation,
+            node.depth,
+            node.cubes,
+            option(node.step.map(|step| format!("\"{}\"", step))),
+            option(node.var.map(|var| var.to_string())),
+            node.elapsed.as_micros()
+        ));
+        write_json_nodes(json, nodes, children, &children[id]);
+        json.push('}');
+    }
+    json.push(']');
+}
+
+#[cfg(test)]
+#[allow(clippy::bool_assert_comparison)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn tracer() {
+        let tracer = Tracer::new();
+        let root = tracer.enter(None, Operation::Tautology, 0, 3);
+        tracer.step(root, Step::Rule(Rule::Split));
+        tracer.split(root, 2);
+        let child = tracer.enter(Some(root), Operation::Tautology, 1, 2);
+        tracer.step(child, Step::Rule(Rule::Unate));
+        tracer.leave(child);
+        let child = tracer.enter(Some(root), Operation::Tautology, 1, 1);
+        tracer.leave(child);
+        tracer.leave(root);
+
+        assert_eq!(tracer.len(), 3);
+        let nodes = tracer.nodes();
+        assert_eq!(nodes[1].parent, Some(0));
+        assert_eq!(nodes[0].var, Some(2));
+        assert_eq!(nodes[2].step, None);
+        assert_eq!(nodes[0].elapsed >= nodes[1].elapsed, true);
+
+        let json = tracer.to_json();
+        assert_eq!(
+            json.starts_with(
+                "[{\"operation\":\"tautology\",\"depth\":0,\"cubes\":3,\"step\":\"split\",\"var\":2,"
+            ),
+            true
+        );
+        assert_eq!(json.contains("\"step\":\"unate\",\"var\":null,"), true);
+        assert_eq!(json.matches("\"children\":[]").count(), 2);
+
+        let folded = tracer.to_folded();
+        let stacks: Vec<&str> = folded
+            .lines()
+            .map(|line| line.rsplit_once(' ').unwrap().0)
+            .collect();
+        assert_eq!(
+            stacks,
+            vec![
+                "tautology split x2",
+                "tautology split x2;tautology",
+                "tautology split x2;tautology unate",
+            ]
+        );
+        tracer.clear();
+        assert_eq!(tracer.is_empty(), true);
+        assert_eq!(tracer.to_json(), "[]");
+    }
+}